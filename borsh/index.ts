@@ -15,7 +15,7 @@ export interface WebAuthnAuthenticator {
 export interface OidcIdentity {
   iss: string;
   aud: string;
-  email_hash: Uint8Array;
+  subject_commitment: Uint8Array;
 }
 
 export type Identity =
@@ -33,7 +33,9 @@ export interface IdentityWithPermissions {
 }
 
 export interface RemoveAccountAction {
-  RemoveAccount: Record<string, never>;
+  RemoveAccount: {
+    refund_to: Uint8Array;
+  };
 }
 
 export interface AddIdentityAction {
@@ -57,16 +59,119 @@ export interface SignAction {
   Sign: SignRequest;
 }
 
+export type OidcKeyType =
+  | { Rsa: Record<string, never> }
+  | { P256: Record<string, never> }
+  | { Ed25519: Record<string, never> };
+
+export interface OidcKeyEntry {
+  pk_hash: Uint8Array;
+  key_type: OidcKeyType;
+  kid: string;
+  not_before: bigint;
+  not_after: bigint;
+}
+
+export interface AddOidcKeyAction {
+  AddOidcKey: {
+    iss_hash: Uint8Array;
+    key_entry: OidcKeyEntry;
+  };
+}
+
+export interface RemoveOidcKeyAction {
+  RemoveOidcKey: {
+    iss_hash: Uint8Array;
+    pk_hash: Uint8Array;
+  };
+}
+
+export interface AcceptOidcAuthoritiesAction {
+  AcceptOidcAuthorities: {
+    iss_hash: Uint8Array;
+  };
+}
+
+export interface ProposeOidcAuthoritiesAction {
+  ProposeOidcAuthorities: {
+    iss_hash: Uint8Array;
+    authorities: Uint8Array[];
+    threshold: number;
+  };
+}
+
+export interface SetOidcClockSkewAction {
+  SetOidcClockSkew: {
+    iss_hash: Uint8Array;
+    clock_skew: bigint;
+  };
+}
+
+export interface SetOidcKeyAddDelayAction {
+  SetOidcKeyAddDelay: {
+    iss_hash: Uint8Array;
+    key_add_delay: bigint;
+  };
+}
+
+export type RentPayer =
+  | { Relayer: Record<string, never> }
+  | { Account: Record<string, never> };
+
+export interface SetRentPayerAction {
+  SetRentPayer: RentPayer;
+}
+
 export type Action =
   | RemoveAccountAction
   | AddIdentityAction
   | RemoveIdentityAction
-  | SignAction;
+  | SignAction
+  | AddOidcKeyAction
+  | RemoveOidcKeyAction
+  | AcceptOidcAuthoritiesAction
+  | SetRentPayerAction
+  | ProposeOidcAuthoritiesAction
+  | SetOidcClockSkewAction
+  | SetOidcKeyAddDelayAction;
+
+export interface TransactionFee {
+  recipient: Uint8Array;
+  lamports: bigint;
+}
+
+export type ReplayProtection =
+  | { Sequential: Record<string, never> }
+  | { Unordered: { expiry_slot: bigint } };
 
 export interface Transaction {
   account_id: bigint;
   nonce: bigint;
   action: Action;
+  fee: TransactionFee | null;
+  relayer: Uint8Array | null;
+  replay_protection: ReplayProtection;
+}
+
+export interface WebAuthnAuthData {
+  client_data: string;
+  authenticator_data: Uint8Array;
+}
+
+export interface Sp1Groth16Proof {
+  proof: Uint8Array;
+  public_values: Uint8Array;
+  vkey_hash: Uint8Array;
+}
+
+export interface ExecuteWebauthnArgs {
+  transaction: Transaction;
+  auth: WebAuthnAuthData;
+}
+
+export interface ExecuteZkOidcArgs {
+  transaction: Transaction;
+  groth16_proof: Sp1Groth16Proof;
 }
 
 export const borshUtils = {
@@ -95,6 +200,14 @@ export const borshUtils = {
     transaction: (transaction: Transaction): Uint8Array => {
       return borsh.serialize(schemas.transactionSchema, transaction);
     },
+
+    executeWebauthnArgs: (args: ExecuteWebauthnArgs): Uint8Array => {
+      return borsh.serialize(schemas.executeWebauthnArgsSchema, args);
+    },
+
+    executeZkOidcArgs: (args: ExecuteZkOidcArgs): Uint8Array => {
+      return borsh.serialize(schemas.executeZkOidcArgsSchema, args);
+    },
   },
 
   deserialize: {
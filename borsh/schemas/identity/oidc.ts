@@ -4,6 +4,6 @@ export const oidcIdentitySchema: Schema = {
   struct: {
     iss: "string",
     aud: "string",
-    email_hash: { array: { type: "u8", len: 32 } },
+    subject_commitment: { array: { type: "u8", len: 32 } },
   },
 };
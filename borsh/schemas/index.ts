@@ -1,4 +1,5 @@
 export * from "./account";
 export * from "./account-manager";
 export * from "./identity";
+export * from "./oidc-key-registry";
 export * from "./transaction";
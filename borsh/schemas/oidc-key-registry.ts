@@ -0,0 +1,19 @@
+import { Schema } from "borsh";
+
+export const oidcKeyTypeSchema: Schema = {
+  enum: [
+    { struct: { Rsa: { struct: {} } } },
+    { struct: { P256: { struct: {} } } },
+    { struct: { Ed25519: { struct: {} } } },
+  ],
+};
+
+export const oidcKeyEntrySchema: Schema = {
+  struct: {
+    pk_hash: { array: { type: "u8", len: 32 } },
+    key_type: oidcKeyTypeSchema,
+    kid: "string",
+    not_before: "u64",
+    not_after: "u64",
+  },
+};
@@ -1,5 +1,6 @@
 import { Schema } from "borsh";
 import { identityWithPermissionsSchema, identitySchema } from "../identity";
+import { oidcKeyEntrySchema } from "../oidc-key-registry";
 
 export const signRequestSchema: Schema = {
   struct: {
@@ -12,12 +13,90 @@ export const signRequestSchema: Schema = {
   },
 };
 
+export const removeAccountRequestSchema: Schema = {
+  struct: {
+    refund_to: { array: { type: "u8", len: 32 } },
+  },
+};
+
+export const rentPayerSchema: Schema = {
+  enum: [
+    { struct: { Relayer: { struct: {} } } },
+    { struct: { Account: { struct: {} } } },
+  ],
+};
+
+const issHashSchema: Schema = { array: { type: "u8", len: 32 } };
+
+export const addOidcKeyRequestSchema: Schema = {
+  struct: {
+    iss_hash: issHashSchema,
+    key_entry: oidcKeyEntrySchema,
+  },
+};
+
+export const removeOidcKeyRequestSchema: Schema = {
+  struct: {
+    iss_hash: issHashSchema,
+    pk_hash: { array: { type: "u8", len: 32 } },
+  },
+};
+
+export const acceptOidcAuthoritiesRequestSchema: Schema = {
+  struct: {
+    iss_hash: issHashSchema,
+  },
+};
+
+export const proposeOidcAuthoritiesRequestSchema: Schema = {
+  struct: {
+    iss_hash: issHashSchema,
+    authorities: { array: { type: { array: { type: "u8", len: 32 } } } },
+    threshold: "u8",
+  },
+};
+
+export const setOidcClockSkewRequestSchema: Schema = {
+  struct: {
+    iss_hash: issHashSchema,
+    clock_skew: "u64",
+  },
+};
+
+export const setOidcKeyAddDelayRequestSchema: Schema = {
+  struct: {
+    iss_hash: issHashSchema,
+    key_add_delay: "u64",
+  },
+};
+
 export const actionSchema: Schema = {
   enum: [
-    { struct: { RemoveAccount: { struct: {} } } },
+    { struct: { RemoveAccount: removeAccountRequestSchema } },
     { struct: { AddIdentity: identityWithPermissionsSchema } },
     { struct: { RemoveIdentity: identitySchema } },
     { struct: { Sign: signRequestSchema } },
+    { struct: { AddOidcKey: addOidcKeyRequestSchema } },
+    { struct: { RemoveOidcKey: removeOidcKeyRequestSchema } },
+    { struct: { AcceptOidcAuthorities: acceptOidcAuthoritiesRequestSchema } },
+    { struct: { SetRentPayer: rentPayerSchema } },
+    { struct: { ProposeOidcAuthorities: proposeOidcAuthoritiesRequestSchema } },
+    { struct: { SetOidcClockSkew: setOidcClockSkewRequestSchema } },
+    { struct: { SetOidcKeyAddDelay: setOidcKeyAddDelayRequestSchema } },
+  ],
+};
+
+export const transactionFeeSchema: Schema = {
+  struct: {
+    recipient: { array: { type: "u8", len: 32 } },
+    lamports: "u64",
+  },
+};
+
+export const replayProtectionSchema: Schema = {
+  enum: [
+    { struct: { Sequential: { struct: {} } } },
+    { struct: { Unordered: { struct: { expiry_slot: "u64" } } } },
   ],
 };
 
@@ -26,5 +105,39 @@ export const transactionSchema: Schema = {
     account_id: "u64",
     nonce: "u128",
     action: actionSchema,
+    fee: { option: transactionFeeSchema },
+    relayer: { option: { array: { type: "u8", len: 32 } } },
+    replay_protection: replayProtectionSchema,
+  },
+};
+
+export const webAuthnAuthDataSchema: Schema = {
+  struct: {
+    client_data: "string",
+    authenticator_data: { array: { type: "u8" } },
+  },
+};
+
+export const sp1Groth16ProofSchema: Schema = {
+  struct: {
+    proof: { array: { type: "u8" } },
+    public_values: { array: { type: "u8" } },
+    vkey_hash: { array: { type: "u8", len: 32 } },
+  },
+};
+
+// Transaction buffer payloads of the `execute_*_from_buffer` instructions.
+
+export const executeWebauthnArgsSchema: Schema = {
+  struct: {
+    transaction: transactionSchema,
+    auth: webAuthnAuthDataSchema,
+  },
+};
+
+export const executeZkOidcArgsSchema: Schema = {
+  struct: {
+    transaction: transactionSchema,
+    groth16_proof: sp1Groth16ProofSchema,
   },
 };
@@ -1,6 +1,10 @@
 use crate::{
     pda_seeds::{ABSTRACT_ACCOUNT_SEED, ACCOUNT_MANAGER_SEED},
-    types::{account::AbstractAccount, account_manager::AccountManager, identity::*},
+    types::{
+        account::{AbstractAccount, RentPayer},
+        account_manager::AccountManager,
+        identity::*,
+    },
 };
 use anchor_lang::prelude::*;
 
@@ -45,7 +49,9 @@ pub fn create_account_impl(
     identity_with_permissions: IdentityWithPermissions,
 ) -> Result<()> {
     ctx.accounts.abstract_account.nonce = 0;
+    ctx.accounts.abstract_account.nonce_keys = vec![];
     ctx.accounts.abstract_account.identities = vec![identity_with_permissions];
+    ctx.accounts.abstract_account.rent_payer = RentPayer::Relayer;
     ctx.accounts.abstract_account.bump = ctx.bumps.abstract_account;
 
     ctx.accounts.account_manager.increment_next_account_id();
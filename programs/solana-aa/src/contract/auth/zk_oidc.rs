@@ -1,33 +1,43 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::hash::hash as sha256;
-use serde::{Deserialize, Serialize};
 use sp1_solana::verify_proof;
+use zk_oidc_types::{AggregatedOutputs, PublicOutputs};
+
+use crate::types::oidc_key_registry::OidcKeyRegistry;
 
 /*
     ZK OIDC authentication.
 
-    An RS256 JWT is verified inside the SP1 zkVM guest program (`zk/jwt-program`),
-    which commits privacy-preserving public outputs (SHA-256 hashes of the email
-    and signing key, plus iss/aud/nonce). The Groth16 wrapper proof is verified
+    An RS256, ES256 or EdDSA JWT is verified inside the SP1 zkVM guest program (`zk/jwt-program`),
+    which commits privacy-preserving public outputs (a salted SHA-256 commitment
+    to the `sub` claim, the SHA-256 of the signing key, plus iss/aud/nonce and
+    the token lifetime). The Groth16 wrapper proof is verified
     here through the alt_bn128 syscalls (sp1-solana), which — unlike `big_mod_exp`
     used by the legacy `rsa` PoC — are enabled on mainnet.
 
     Trust chain enforced by `execute_zk_oidc`:
-    1. Groth16 proof valid for `JWT_VKEY_HASH` -> the exact guest binary ran and
-       the JWT signature verified against the committed `pk_hash` key.
+    1. `vkey_hash` active in the `ZkVkeyRegistry` and the Groth16 proof valid
+       for it -> an accepted guest binary ran and the JWT signature verified
+       against the committed `pk_hash` key.
     2. `(iss, pk_hash)` present in the on-chain `OidcKeyRegistry` -> the JWT was
        signed by a key the registry authority pinned for that provider.
     3. JWT `nonce` == hex(sha256(borsh(transaction))) -> the user approved this
        exact transaction when the token was minted (proof/transaction binding).
+       The claim it is read from, and any provider-side hashing, follow the
+       issuer's `OidcNonceBinding` in the registry.
+    4. JWT `exp` (plus the registry's clock skew) not before the `Clock` unix
+       timestamp -> the proof authorizes only for as long as the provider
+       intended the token to live.
 
     The guest commits hashes as plain `[u8; 32]`, so this module needs no SP1
-    field-type dependencies and is insulated from zkVM version churn.
-*/
+    field-type dependencies and is insulated from zkVM version churn. The
+    public values layout lives in the shared `zk-oidc-types` crate the guest
+    encodes with, so the two cannot drift apart.
 
-/// Verification key hash of `zk/jwt-program`.
-/// Regenerate with `cd zk/script && cargo run --release -- vkey` after any guest change.
-pub const JWT_VKEY_HASH: &str =
-    "0x004dec4cfca4ca67f42cdbfffdeb2abb12aa0fb08305bdde847de33295b6fb23";
+    `execute_zk_oidc_aggregated` runs the same checks on every JWT of one
+    aggregated proof (`jwt-aggregate` guest), so several OIDC identities can
+    co-approve a transaction for the cost of a single Groth16 verification.
+*/
 
 /// SP1 universal Groth16 verification key, copied from the prover's circuit
 /// artifacts (`~/.sp1/circuits/groth16/<version>/groth16_vk.bin`). Must match
@@ -38,63 +48,164 @@ const GROTH16_VK_BYTES: &[u8] = include_bytes!("groth16_vk.bin");
 // account storage. Google's iss is 30 chars; aud (OAuth client id) ~73.
 const MAX_ISS_LEN: usize = 64;
 const MAX_AUD_LEN: usize = 256;
+const MAX_NONCE_CLAIM_LEN: usize = 64;
+/// Bounds the per-JWT registry checks and identity lookups one instruction runs.
+pub const MAX_AGGREGATED_JWTS: usize = 4;
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct Sp1Groth16Proof {
     pub proof: Vec<u8>,
     pub public_values: Vec<u8>,
-}
-
-/// Mirror of the guest program's `PublicOutputs` (bincode field order must match
-/// `zk/jwt-program/src/main.rs`). The golden fixture test catches drift.
-#[derive(Serialize, Deserialize)]
-struct PublicOutputs {
-    email_hash: [u8; 32],
-    pk_hash: [u8; 32],
-    iss: String,
-    aud: String,
-    nonce: String,
+    /// Verification key hash of the guest that produced the proof
+    /// (`cd zk/script && cargo run --release -- vkey`). Must be active in the
+    /// `ZkVkeyRegistry`; the caller checks membership before verification.
+    pub vkey_hash: [u8; 32],
 }
 
 /// Facts established by a verified proof.
 pub struct VerifiedJwt {
-    pub email_hash: [u8; 32],
+    pub subject_commitment: [u8; 32],
     pub pk_hash: [u8; 32],
     pub iss: String,
     pub aud: String,
     pub nonce: String,
+    pub iat: u64,
+    pub exp: u64,
+    pub alg: String,
+    pub nonce_claim: String,
+}
+
+impl VerifiedJwt {
+    /// Whether the token is inside its `[iat, exp]` lifetime at `now` (unix
+    /// seconds), widened by `clock_skew` on both ends to absorb drift between
+    /// the provider's clock and the cluster's.
+    pub fn is_live_at(&self, now: u64, clock_skew: u64) -> bool {
+        self.iat <= now.saturating_add(clock_skew) && now <= self.exp.saturating_add(clock_skew)
+    }
 }
 
 pub fn verify_zk_oidc_proof(groth16_proof: &Sp1Groth16Proof) -> Result<VerifiedJwt> {
+    verify_groth16_proof(groth16_proof)?;
+
+    // Safe to parse only after verification: the Groth16 proof commits to the
+    // digest of `public_values`, so these bytes are exactly what the guest wrote.
+    let outputs = PublicOutputs::decode(&groth16_proof.public_values)
+        .map_err(|_| ErrorCode::InvalidPublicValues)?;
+    verified_jwt(outputs)
+}
+
+/// Verifies a `jwt-aggregate` proof, one `VerifiedJwt` per JWT in the order
+/// the guest received them.
+pub fn verify_zk_oidc_aggregated_proof(
+    groth16_proof: &Sp1Groth16Proof,
+) -> Result<Vec<VerifiedJwt>> {
+    verify_groth16_proof(groth16_proof)?;
+
+    let AggregatedOutputs { outputs } = AggregatedOutputs::decode(&groth16_proof.public_values)
+        .map_err(|_| ErrorCode::InvalidPublicValues)?;
+    require!(
+        !outputs.is_empty() && outputs.len() <= MAX_AGGREGATED_JWTS,
+        ErrorCode::InvalidAggregatedJwtCount
+    );
+
+    outputs.into_iter().map(verified_jwt).collect()
+}
+
+fn verify_groth16_proof(groth16_proof: &Sp1Groth16Proof) -> Result<()> {
+    let vkey_hash = format!("0x{}", hex::encode(groth16_proof.vkey_hash));
     verify_proof(
         &groth16_proof.proof,
         &groth16_proof.public_values,
-        JWT_VKEY_HASH,
+        &vkey_hash,
         GROTH16_VK_BYTES,
     )
     .map_err(|_| ErrorCode::ProofVerificationFailed)?;
 
-    // Safe to parse only after verification: the Groth16 proof commits to the
-    // digest of `public_values`, so these bytes are exactly what the guest wrote.
-    let outputs: PublicOutputs = bincode::deserialize(&groth16_proof.public_values)
-        .map_err(|_| ErrorCode::InvalidPublicValues)?;
+    Ok(())
+}
 
-    require!(outputs.iss.len() <= MAX_ISS_LEN, ErrorCode::IssuerTooLong);
-    require!(outputs.aud.len() <= MAX_AUD_LEN, ErrorCode::AudienceTooLong);
+/// Applies the length caps to verified outputs.
+fn verified_jwt(outputs: PublicOutputs) -> Result<VerifiedJwt> {
+    let PublicOutputs {
+        subject_commitment,
+        pk_hash,
+        iss,
+        aud,
+        nonce,
+        iat,
+        exp,
+        alg,
+        nonce_claim,
+    } = outputs;
+
+    require!(iss.len() <= MAX_ISS_LEN, ErrorCode::IssuerTooLong);
+    require!(aud.len() <= MAX_AUD_LEN, ErrorCode::AudienceTooLong);
+    require!(
+        nonce_claim.len() <= MAX_NONCE_CLAIM_LEN,
+        ErrorCode::NonceClaimTooLong
+    );
 
     Ok(VerifiedJwt {
-        email_hash: outputs.email_hash,
-        pk_hash: outputs.pk_hash,
-        iss: outputs.iss,
-        aud: outputs.aud,
-        nonce: outputs.nonce,
+        subject_commitment,
+        pk_hash,
+        iss,
+        aud,
+        nonce,
+        iat,
+        exp,
+        alg,
+        nonce_claim,
     })
 }
 
-/// The JWT nonce format that binds a token to one transaction:
-/// lowercase hex of sha256 over the Borsh-serialized `Transaction`.
+/// Trust chain steps 2-4 for a verified JWT: the issuer's `registry` accepts
+/// it for the transaction whose nonce is `transaction_nonce`, at `now` (unix
+/// seconds). Public so the host tooling runs the exact same checks.
+pub fn check_oidc_registry(
+    jwt: &VerifiedJwt,
+    registry: &OidcKeyRegistry,
+    transaction_nonce: &str,
+    now: u64,
+) -> Result<()> {
+    // JWKS pinning: only proofs against keys the proven issuer's registry
+    // currently accepts count, and only under the algorithm recorded for them.
+    require!(registry.iss == jwt.iss, ErrorCode::OidcIssuerMismatch);
+    let key_entry = registry
+        .find(&jwt.pk_hash)
+        .ok_or(ErrorCode::OidcKeyNotRegistered)?;
+    require!(key_entry.is_valid_at(now), ErrorCode::OidcKeyNotActive);
+    require!(
+        key_entry.key_type.jwt_alg() == jwt.alg,
+        ErrorCode::OidcKeyTypeMismatch
+    );
+
+    // Proof/transaction binding: the JWT was minted with the hash of this exact
+    // transaction as its nonce, so the proof authorizes nothing else. The
+    // issuer's binding mode says which claim carries it and in what form.
+    let nonce_binding = &registry.nonce_binding;
+    require!(
+        jwt.nonce_claim == nonce_binding.claim_name(),
+        ErrorCode::NonceClaimMismatch
+    );
+    require!(
+        jwt.nonce == nonce_binding.expected_claim_value(transaction_nonce),
+        ErrorCode::TransactionBindingMismatch
+    );
+
+    // Token lifetime: the proof only authorizes while the JWT it attests to is
+    // live, so a leaked proof stops working when the provider meant it to.
+    require!(
+        jwt.is_live_at(now, registry.clock_skew),
+        ErrorCode::OidcTokenExpired
+    );
+
+    Ok(())
+}
+
+/// The JWT nonce that binds a token to this Borsh-serialized `Transaction`,
+/// hashed with the sha256 syscall.
 pub fn transaction_nonce_hex(transaction_bytes: &[u8]) -> String {
-    hex::encode(sha256(transaction_bytes).to_bytes())
+    zk_oidc_types::transaction_nonce_hex(&sha256(transaction_bytes).to_bytes())
 }
 
 #[error_code]
@@ -107,4 +218,22 @@ pub enum ErrorCode {
     IssuerTooLong,
     #[msg("Audience exceeds maximum length")]
     AudienceTooLong,
+    #[msg("Nonce claim name exceeds maximum length")]
+    NonceClaimTooLong,
+    #[msg("Aggregated proof carries no JWTs or more than the maximum")]
+    InvalidAggregatedJwtCount,
+    #[msg("JWT nonce does not match the transaction hash")]
+    TransactionBindingMismatch,
+    #[msg("JWT nonce was read from a claim the issuer is not configured for")]
+    NonceClaimMismatch,
+    #[msg("OIDC registry does not belong to the JWT issuer")]
+    OidcIssuerMismatch,
+    #[msg("OIDC signing key not present in the registry")]
+    OidcKeyNotRegistered,
+    #[msg("OIDC signing key is outside its validity window")]
+    OidcKeyNotActive,
+    #[msg("JWT algorithm does not match the registered OIDC key type")]
+    OidcKeyTypeMismatch,
+    #[msg("OIDC token is expired or not yet valid")]
+    OidcTokenExpired,
 }
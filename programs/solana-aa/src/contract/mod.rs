@@ -1,6 +1,9 @@
 pub mod accounts;
 pub mod auth;
 pub mod contract_lifecycle;
+pub mod nonce_bitmap;
 pub mod oidc_registry;
+pub mod paymaster;
 pub mod transaction;
 pub mod transaction_buffer;
+pub mod zk_vkey_registry;
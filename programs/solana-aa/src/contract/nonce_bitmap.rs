@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    pda_seeds::{ABSTRACT_ACCOUNT_SEED, NONCE_BITMAP_SEED},
+    types::{
+        account::{AbstractAccount, AccountId},
+        nonce_bitmap::NonceBitmap,
+    },
+};
+
+/// Anyone may create an account's bitmap: it only ever restricts which
+/// unordered nonces the account's own signed transactions may use.
+#[derive(Accounts)]
+#[instruction(account_id: AccountId)]
+pub struct InitNonceBitmap<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        seeds = [ABSTRACT_ACCOUNT_SEED, account_id.to_le_bytes().as_ref()],
+        bump = abstract_account.bump,
+    )]
+    pub abstract_account: Account<'info, AbstractAccount>,
+
+    #[account(
+        init,
+        payer = signer,
+        space = NonceBitmap::SIZE,
+        seeds = [NONCE_BITMAP_SEED, account_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub nonce_bitmap: Account<'info, NonceBitmap>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn init_nonce_bitmap_impl(ctx: Context<InitNonceBitmap>, account_id: AccountId) -> Result<()> {
+    let nonce_bitmap = &mut ctx.accounts.nonce_bitmap;
+    nonce_bitmap.account_id = account_id;
+    nonce_bitmap.base = 0;
+    nonce_bitmap.bits = Default::default();
+    nonce_bitmap.payer = ctx.accounts.signer.key();
+    nonce_bitmap.bump = ctx.bumps.nonce_bitmap;
+
+    Ok(())
+}
+
+/// Anyone may close a bitmap once its account is closed, refunding whoever
+/// funded it. Account IDs are never reused, so the bitmap can no longer be
+/// needed. This covers accounts closed without passing their bitmap.
+#[derive(Accounts)]
+#[instruction(account_id: AccountId)]
+pub struct CloseNonceBitmap<'info> {
+    /// CHECK: The bitmap's abstract account, which must already be closed.
+    #[account(
+        seeds = [ABSTRACT_ACCOUNT_SEED, account_id.to_le_bytes().as_ref()],
+        bump,
+        constraint = abstract_account.data_is_empty() @ ErrorCode::AccountStillOpen,
+    )]
+    pub abstract_account: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [NONCE_BITMAP_SEED, account_id.to_le_bytes().as_ref()],
+        bump = nonce_bitmap.bump,
+        has_one = payer,
+        close = payer,
+    )]
+    pub nonce_bitmap: Account<'info, NonceBitmap>,
+
+    /// CHECK: Receives the rent; must be the bitmap's recorded payer.
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("The bitmap's abstract account is still open")]
+    AccountStillOpen,
+}
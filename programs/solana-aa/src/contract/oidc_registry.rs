@@ -1,20 +1,52 @@
 use anchor_lang::prelude::*;
 
 use crate::{
-    pda_seeds::OIDC_KEY_REGISTRY_SEED,
-    types::oidc_key_registry::{OidcKeyEntry, OidcKeyRegistry},
+    pda_seeds::{ACCOUNT_MANAGER_SEED, OIDC_KEY_REGISTRY_SEED},
+    types::{
+        account::AbstractAccountOperationAccounts,
+        account_manager::AccountManager,
+        oidc_key_registry::{OidcKeyEntry, OidcKeyRegistry, OidcNonceBinding},
+        transaction::transaction::{
+            AcceptOidcAuthoritiesRequest, AddOidcKeyRequest, ProposeOidcAuthoritiesRequest,
+            RemoveOidcKeyRequest, SetOidcClockSkewRequest, SetOidcKeyAddDelayRequest,
+        },
+    },
+    utils::pda::realloc_account,
 };
 
+// Google's iss is 30 chars; matches the cap applied to proven `iss` values.
+const MAX_ISS_LEN: usize = 64;
+
+// Governance instructions take the signing `authority` (which also pays for
+// reallocation) plus any co-signing authorities as signer remaining accounts;
+// together they must meet the registry threshold.
+
 #[derive(Accounts)]
+#[instruction(iss_hash: [u8; 32], iss: String, clock_skew: u64, nonce_binding: OidcNonceBinding)]
 pub struct InitOidcRegistry<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
 
+    // Whoever creates an issuer's registry chooses its keys, so only the
+    // deployment admin may, or anyone could claim an issuer and forge its
+    // identities.
+    #[account(
+        seeds = [ACCOUNT_MANAGER_SEED],
+        bump = account_manager.bump,
+        constraint = account_manager.admin == authority.key() @ ErrorCode::NotAdmin,
+    )]
+    pub account_manager: Account<'info, AccountManager>,
+
+    // Starts as a 1-of-1 set of `authority`; multisig governance is set up
+    // through propose/accept_oidc_authorities.
     #[account(
         init,
         payer = authority,
-        space = OidcKeyRegistry::INIT_SIZE,
-        seeds = [OIDC_KEY_REGISTRY_SEED],
+        space = OidcKeyRegistry::INIT_SIZE
+            + OidcKeyRegistry::AUTHORITY_SIZE
+            + iss.len()
+            + nonce_binding.byte_size(),
+        seeds = [OIDC_KEY_REGISTRY_SEED, iss_hash.as_ref()],
         bump,
     )]
     pub oidc_key_registry: Account<'info, OidcKeyRegistry>,
@@ -30,9 +62,8 @@ pub struct AddOidcKey<'info> {
 
     #[account(
         mut,
-        seeds = [OIDC_KEY_REGISTRY_SEED],
+        seeds = [OIDC_KEY_REGISTRY_SEED, oidc_key_registry.iss_hash.as_ref()],
         bump = oidc_key_registry.bump,
-        has_one = authority,
         realloc = oidc_key_registry.to_account_info().data_len() + key_entry.byte_size(),
         realloc::payer = authority,
         realloc::zero = false,
@@ -43,16 +74,90 @@ pub struct AddOidcKey<'info> {
 }
 
 #[derive(Accounts)]
+#[instruction(pk_hash: [u8; 32])]
 pub struct RemoveOidcKey<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    // Shrinks by the removed entry and refunds its rent to the authority, so a
+    // registry's size tracks the provider's live JWKS instead of its history.
+    // An unknown `pk_hash` leaves the size unchanged and fails in the handler.
+    #[account(
+        mut,
+        seeds = [OIDC_KEY_REGISTRY_SEED, oidc_key_registry.iss_hash.as_ref()],
+        bump = oidc_key_registry.bump,
+        realloc = oidc_key_registry.to_account_info().data_len()
+            - oidc_key_registry.find(&pk_hash).map_or(0, |key| key.byte_size()),
+        realloc::payer = authority,
+        realloc::zero = false,
+    )]
+    pub oidc_key_registry: Account<'info, OidcKeyRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(authorities: Vec<Pubkey>)]
+pub struct ProposeOidcAuthorities<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [OIDC_KEY_REGISTRY_SEED, oidc_key_registry.iss_hash.as_ref()],
+        bump = oidc_key_registry.bump,
+        realloc = oidc_key_registry.to_account_info().data_len()
+            - oidc_key_registry.pending_authorities.len() * OidcKeyRegistry::AUTHORITY_SIZE
+            + authorities.len() * OidcKeyRegistry::AUTHORITY_SIZE,
+        realloc::payer = authority,
+        realloc::zero = false,
+    )]
+    pub oidc_key_registry: Account<'info, OidcKeyRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptOidcAuthorities<'info> {
+    #[account(mut)]
     pub authority: Signer<'info>,
 
-    // Capacity is intentionally kept on removal: registries are small and keys
-    // rotate, so the space is reused by the next addition.
+    // The pending set moves into `authorities` and its old slot is cleared, so
+    // the account loses the size of the outgoing set.
     #[account(
         mut,
-        seeds = [OIDC_KEY_REGISTRY_SEED],
+        seeds = [OIDC_KEY_REGISTRY_SEED, oidc_key_registry.iss_hash.as_ref()],
+        bump = oidc_key_registry.bump,
+        realloc = oidc_key_registry.to_account_info().data_len()
+            - oidc_key_registry.authorities.len() * OidcKeyRegistry::AUTHORITY_SIZE,
+        realloc::payer = authority,
+        realloc::zero = false,
+    )]
+    pub oidc_key_registry: Account<'info, OidcKeyRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetOidcClockSkew<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [OIDC_KEY_REGISTRY_SEED, oidc_key_registry.iss_hash.as_ref()],
+        bump = oidc_key_registry.bump,
+    )]
+    pub oidc_key_registry: Account<'info, OidcKeyRegistry>,
+}
+
+#[derive(Accounts)]
+pub struct SetOidcKeyAddDelay<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [OIDC_KEY_REGISTRY_SEED, oidc_key_registry.iss_hash.as_ref()],
         bump = oidc_key_registry.bump,
-        has_one = authority,
     )]
     pub oidc_key_registry: Account<'info, OidcKeyRegistry>,
 }
@@ -64,44 +169,389 @@ pub struct CloseOidcRegistry<'info> {
 
     #[account(
         mut,
-        seeds = [OIDC_KEY_REGISTRY_SEED],
+        seeds = [OIDC_KEY_REGISTRY_SEED, oidc_key_registry.iss_hash.as_ref()],
         bump = oidc_key_registry.bump,
-        has_one = authority,
         close = authority,
     )]
     pub oidc_key_registry: Account<'info, OidcKeyRegistry>,
 }
 
-pub fn init_oidc_registry_impl(ctx: Context<InitOidcRegistry>) -> Result<()> {
-    ctx.accounts.oidc_key_registry.authority = ctx.accounts.authority.key();
-    ctx.accounts.oidc_key_registry.keys = vec![];
-    ctx.accounts.oidc_key_registry.bump = ctx.bumps.oidc_key_registry;
+/// Keys approving a change: `authority` plus any signer `co_signers`.
+fn approvers(authority: Pubkey, co_signers: &[AccountInfo]) -> Vec<Pubkey> {
+    std::iter::once(authority)
+        .chain(
+            co_signers
+                .iter()
+                .filter(|account| account.is_signer)
+                .map(|account| account.key()),
+        )
+        .collect()
+}
+
+pub fn init_oidc_registry_impl(
+    ctx: Context<InitOidcRegistry>,
+    iss_hash: [u8; 32],
+    iss: String,
+    clock_skew: u64,
+    nonce_binding: OidcNonceBinding,
+    key_add_delay: u64,
+) -> Result<()> {
+    require!(iss.len() <= MAX_ISS_LEN, ErrorCode::IssuerTooLong);
+    require!(
+        iss_hash == OidcKeyRegistry::iss_hash(&iss),
+        ErrorCode::IssuerHashMismatch
+    );
+    require!(
+        clock_skew <= OidcKeyRegistry::MAX_CLOCK_SKEW,
+        ErrorCode::ClockSkewTooLarge
+    );
+    require!(
+        key_add_delay <= OidcKeyRegistry::MAX_KEY_ADD_DELAY,
+        ErrorCode::KeyAddDelayTooLarge
+    );
+
+    let registry = &mut ctx.accounts.oidc_key_registry;
+    registry.authorities = vec![ctx.accounts.authority.key()];
+    registry.threshold = 1;
+    registry.pending_authorities = vec![];
+    registry.pending_threshold = 0;
+    registry.key_add_delay = key_add_delay;
+    registry.iss_hash = iss_hash;
+    registry.iss = iss;
+    registry.clock_skew = clock_skew;
+    registry.nonce_binding = nonce_binding;
+    registry.keys = vec![];
+    registry.bump = ctx.bumps.oidc_key_registry;
 
     Ok(())
 }
 
+/// First step of an authority transfer: the current set proposes a new one,
+/// replacing any earlier proposal. Nothing changes until it is accepted.
+pub fn propose_oidc_authorities_impl(
+    ctx: Context<ProposeOidcAuthorities>,
+    authorities: Vec<Pubkey>,
+    threshold: u8,
+) -> Result<()> {
+    let approvers = approvers(ctx.accounts.authority.key(), ctx.remaining_accounts);
+    propose_authorities(
+        &mut ctx.accounts.oidc_key_registry,
+        &approvers,
+        authorities,
+        threshold,
+    )
+}
+
+/// Second step of an authority transfer: the proposed set proves control by
+/// meeting its own threshold, so a typo in the proposal cannot brick the
+/// registry.
+pub fn accept_oidc_authorities_impl(ctx: Context<AcceptOidcAuthorities>) -> Result<()> {
+    let approvers = approvers(ctx.accounts.authority.key(), ctx.remaining_accounts);
+    accept_authorities(&mut ctx.accounts.oidc_key_registry, &approvers)
+}
+
 pub fn add_oidc_key_impl(ctx: Context<AddOidcKey>, key_entry: OidcKeyEntry) -> Result<()> {
-    let registry = &mut ctx.accounts.oidc_key_registry;
+    let approvers = approvers(ctx.accounts.authority.key(), ctx.remaining_accounts);
+    add_key(&mut ctx.accounts.oidc_key_registry, &approvers, key_entry)
+}
+
+/// Removals skip the timelock so a compromised or retired provider key can be
+/// revoked at once.
+pub fn remove_oidc_key_impl(ctx: Context<RemoveOidcKey>, pk_hash: [u8; 32]) -> Result<()> {
+    let approvers = approvers(ctx.accounts.authority.key(), ctx.remaining_accounts);
+    remove_key(&mut ctx.accounts.oidc_key_registry, &approvers, pk_hash)
+}
+
+// Registry changes shared by the instruction handlers above and the
+// account-governed `Action`s below.
 
+fn propose_authorities(
+    registry: &mut OidcKeyRegistry,
+    approvers: &[Pubkey],
+    authorities: Vec<Pubkey>,
+    threshold: u8,
+) -> Result<()> {
     require!(
-        !registry.contains(&key_entry.iss, &key_entry.pk_hash),
+        registry.is_approved_by(approvers),
+        ErrorCode::InsufficientApprovals
+    );
+    require!(
+        OidcKeyRegistry::is_valid_authority_set(&authorities, threshold),
+        ErrorCode::InvalidAuthoritySet
+    );
+
+    registry.pending_authorities = authorities;
+    registry.pending_threshold = threshold;
+
+    Ok(())
+}
+
+fn accept_authorities(
+    registry: &mut OidcKeyRegistry,
+    approvers: &[Pubkey],
+) -> Result<()> {
+    require!(
+        registry.is_accepted_by(approvers),
+        ErrorCode::InsufficientApprovals
+    );
+
+    registry.authorities = std::mem::take(&mut registry.pending_authorities);
+    registry.threshold = registry.pending_threshold;
+    registry.pending_threshold = 0;
+
+    Ok(())
+}
+
+fn add_key(
+    registry: &mut OidcKeyRegistry,
+    approvers: &[Pubkey],
+    mut key_entry: OidcKeyEntry,
+) -> Result<()> {
+    require!(
+        registry.is_approved_by(approvers),
+        ErrorCode::InsufficientApprovals
+    );
+    require!(
+        !registry.contains(&key_entry.pk_hash),
         ErrorCode::OidcKeyAlreadyRegistered
     );
+    require!(
+        key_entry.kid.len() <= OidcKeyRegistry::MAX_KID_LEN,
+        ErrorCode::KidTooLong
+    );
+
+    // Timelock: the key sits in the registry, inert and removable, for at
+    // least `key_add_delay` before any proof against it is accepted.
+    let now = u64::try_from(Clock::get()?.unix_timestamp).unwrap_or(0);
+    key_entry.not_before = key_entry
+        .not_before
+        .max(now.saturating_add(registry.key_add_delay));
+    require!(
+        key_entry.not_before < key_entry.not_after,
+        ErrorCode::InvalidOidcKeyWindow
+    );
 
     registry.keys.push(key_entry);
 
     Ok(())
 }
 
-pub fn remove_oidc_key_impl(ctx: Context<RemoveOidcKey>, key_entry: OidcKeyEntry) -> Result<()> {
-    let registry = &mut ctx.accounts.oidc_key_registry;
+fn set_clock_skew(
+    registry: &mut OidcKeyRegistry,
+    approvers: &[Pubkey],
+    clock_skew: u64,
+) -> Result<()> {
+    require!(
+        registry.is_approved_by(approvers),
+        ErrorCode::InsufficientApprovals
+    );
+    require!(
+        clock_skew <= OidcKeyRegistry::MAX_CLOCK_SKEW,
+        ErrorCode::ClockSkewTooLarge
+    );
+
+    registry.clock_skew = clock_skew;
+
+    Ok(())
+}
+
+/// The delay can only grow: lowering it would let the authorities skip the
+/// timelock on their next key addition.
+fn set_key_add_delay(
+    registry: &mut OidcKeyRegistry,
+    approvers: &[Pubkey],
+    key_add_delay: u64,
+) -> Result<()> {
+    require!(
+        registry.is_approved_by(approvers),
+        ErrorCode::InsufficientApprovals
+    );
+    require!(
+        key_add_delay >= registry.key_add_delay,
+        ErrorCode::KeyAddDelayDecrease
+    );
+    require!(
+        key_add_delay <= OidcKeyRegistry::MAX_KEY_ADD_DELAY,
+        ErrorCode::KeyAddDelayTooLarge
+    );
 
+    registry.key_add_delay = key_add_delay;
+
+    Ok(())
+}
+
+fn remove_key(
+    registry: &mut OidcKeyRegistry,
+    approvers: &[Pubkey],
+    pk_hash: [u8; 32],
+) -> Result<()> {
     require!(
-        registry.contains(&key_entry.iss, &key_entry.pk_hash),
-        ErrorCode::OidcKeyNotFound
+        registry.is_approved_by(approvers),
+        ErrorCode::InsufficientApprovals
     );
+    require!(registry.contains(&pk_hash), ErrorCode::OidcKeyNotFound);
+
+    registry.keys.retain(|key| key.pk_hash != pk_hash);
+
+    Ok(())
+}
+
+pub(crate) fn add_oidc_key_as_account<'info>(
+    operation_accounts: AbstractAccountOperationAccounts<'_, 'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    request: AddOidcKeyRequest,
+) -> Result<()> {
+    update_oidc_registry_as_account(
+        operation_accounts,
+        remaining_accounts,
+        request.iss_hash,
+        |registry, approvers| add_key(registry, approvers, request.key_entry),
+    )
+}
+
+pub(crate) fn remove_oidc_key_as_account<'info>(
+    operation_accounts: AbstractAccountOperationAccounts<'_, 'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    request: RemoveOidcKeyRequest,
+) -> Result<()> {
+    update_oidc_registry_as_account(
+        operation_accounts,
+        remaining_accounts,
+        request.iss_hash,
+        |registry, approvers| remove_key(registry, approvers, request.pk_hash),
+    )
+}
+
+pub(crate) fn accept_oidc_authorities_as_account<'info>(
+    operation_accounts: AbstractAccountOperationAccounts<'_, 'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    request: AcceptOidcAuthoritiesRequest,
+) -> Result<()> {
+    update_oidc_registry_as_account(
+        operation_accounts,
+        remaining_accounts,
+        request.iss_hash,
+        accept_authorities,
+    )
+}
+
+pub(crate) fn propose_oidc_authorities_as_account<'info>(
+    operation_accounts: AbstractAccountOperationAccounts<'_, 'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    request: ProposeOidcAuthoritiesRequest,
+) -> Result<()> {
+    update_oidc_registry_as_account(
+        operation_accounts,
+        remaining_accounts,
+        request.iss_hash,
+        |registry, approvers| {
+            propose_authorities(registry, approvers, request.authorities, request.threshold)
+        },
+    )
+}
+
+pub(crate) fn set_oidc_clock_skew_as_account<'info>(
+    operation_accounts: AbstractAccountOperationAccounts<'_, 'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    request: SetOidcClockSkewRequest,
+) -> Result<()> {
+    update_oidc_registry_as_account(
+        operation_accounts,
+        remaining_accounts,
+        request.iss_hash,
+        |registry, approvers| set_clock_skew(registry, approvers, request.clock_skew),
+    )
+}
+
+pub(crate) fn set_oidc_key_add_delay_as_account<'info>(
+    operation_accounts: AbstractAccountOperationAccounts<'_, 'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    request: SetOidcKeyAddDelayRequest,
+) -> Result<()> {
+    update_oidc_registry_as_account(
+        operation_accounts,
+        remaining_accounts,
+        request.iss_hash,
+        |registry, approvers| set_key_add_delay(registry, approvers, request.key_add_delay),
+    )
+}
 
-    registry.keys.retain(|key| key != &key_entry);
+/// Applies `update` to the registry of `iss_hash` on behalf of an abstract
+/// account. The account PDA cannot sign, so being the dispatching account
+/// counts as its approval; `remaining_accounts` must be
+/// `[oidc_key_registry, co-signing authorities...]`. The registry is resized to
+/// its new contents, with the execute signer paying or refunded.
+fn update_oidc_registry_as_account<'info>(
+    operation_accounts: AbstractAccountOperationAccounts<'_, 'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    iss_hash: [u8; 32],
+    update: impl FnOnce(&mut OidcKeyRegistry, &[Pubkey]) -> Result<()>,
+) -> Result<()> {
+    let [registry_info, co_signers @ ..] = remaining_accounts else {
+        return Err(ErrorCode::InvalidOidcRegistryAccount.into());
+    };
+
+    // Owner + discriminator identify a registry PDA of this program; the
+    // stored `iss_hash` (its seed) pins the one the transaction signed for.
+    require_keys_eq!(
+        *registry_info.owner,
+        crate::ID,
+        ErrorCode::InvalidOidcRegistryAccount
+    );
+    let mut registry =
+        OidcKeyRegistry::try_deserialize(&mut &registry_info.try_borrow_data()?[..])?;
+    require!(
+        registry.iss_hash == iss_hash,
+        ErrorCode::InvalidOidcRegistryAccount
+    );
+
+    let approvers = approvers(operation_accounts.abstract_account.key(), co_signers);
+    update(&mut registry, &approvers)?;
+
+    let mut data = Vec::new();
+    registry.try_serialize(&mut data)?;
+    realloc_account(
+        registry_info,
+        data.len(),
+        &operation_accounts.signer_info,
+        &operation_accounts.system_program_info,
+    )?;
+    registry_info.try_borrow_mut_data()?.copy_from_slice(&data);
+
+    Ok(())
+}
+
+pub fn set_oidc_clock_skew_impl(ctx: Context<SetOidcClockSkew>, clock_skew: u64) -> Result<()> {
+    let approvers = approvers(ctx.accounts.authority.key(), ctx.remaining_accounts);
+    set_clock_skew(&mut ctx.accounts.oidc_key_registry, &approvers, clock_skew)
+}
+
+pub fn set_oidc_key_add_delay_impl(
+    ctx: Context<SetOidcKeyAddDelay>,
+    key_add_delay: u64,
+) -> Result<()> {
+    let approvers = approvers(ctx.accounts.authority.key(), ctx.remaining_accounts);
+    set_key_add_delay(
+        &mut ctx.accounts.oidc_key_registry,
+        &approvers,
+        key_add_delay,
+    )
+}
+
+/// Only an empty registry can be closed. Keys must go first through
+/// `remove_oidc_key`, in plain view, so a close and re-init cannot quietly
+/// swap keys in under a lower `key_add_delay`.
+pub fn close_oidc_registry_impl(ctx: Context<CloseOidcRegistry>) -> Result<()> {
+    let approvers = approvers(ctx.accounts.authority.key(), ctx.remaining_accounts);
+
+    require!(
+        ctx.accounts.oidc_key_registry.is_approved_by(&approvers),
+        ErrorCode::InsufficientApprovals
+    );
+    require!(
+        ctx.accounts.oidc_key_registry.keys.is_empty(),
+        ErrorCode::RegistryNotEmpty
+    );
 
     Ok(())
 }
@@ -112,4 +562,28 @@ pub enum ErrorCode {
     OidcKeyAlreadyRegistered,
     #[msg("OIDC key not found")]
     OidcKeyNotFound,
+    #[msg("Clock skew exceeds the maximum allowed")]
+    ClockSkewTooLarge,
+    #[msg("Issuer exceeds maximum length")]
+    IssuerTooLong,
+    #[msg("Issuer hash does not match sha256(iss)")]
+    IssuerHashMismatch,
+    #[msg("Key id exceeds maximum length")]
+    KidTooLong,
+    #[msg("OIDC key must become valid before it expires")]
+    InvalidOidcKeyWindow,
+    #[msg("Not enough registry authorities signed")]
+    InsufficientApprovals,
+    #[msg("Authority set must hold 1-10 distinct keys and a threshold between 1 and their count")]
+    InvalidAuthoritySet,
+    #[msg("Key-add delay exceeds the maximum allowed")]
+    KeyAddDelayTooLarge,
+    #[msg("Key-add delay can only be increased")]
+    KeyAddDelayDecrease,
+    #[msg("OIDC registry actions require the issuer's registry as the first remaining account")]
+    InvalidOidcRegistryAccount,
+    #[msg("Only the deployment admin may create a registry")]
+    NotAdmin,
+    #[msg("Remove every key before closing the registry")]
+    RegistryNotEmpty,
 }
@@ -0,0 +1,265 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    pda_seeds::PAYMASTER_SEED,
+    types::{
+        account::AccountId,
+        paymaster::{Paymaster, SponsoredAccount},
+    },
+};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+#[derive(Accounts)]
+pub struct InitPaymaster<'info> {
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+
+    #[account(
+        init,
+        payer = sponsor,
+        space = Paymaster::INIT_SIZE,
+        seeds = [PAYMASTER_SEED, sponsor.key().as_ref()],
+        bump,
+    )]
+    pub paymaster: Account<'info, Paymaster>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePaymaster<'info> {
+    pub sponsor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [PAYMASTER_SEED, sponsor.key().as_ref()],
+        bump = paymaster.bump,
+        has_one = sponsor,
+    )]
+    pub paymaster: Account<'info, Paymaster>,
+}
+
+#[derive(Accounts)]
+pub struct AddSponsoredAccount<'info> {
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [PAYMASTER_SEED, sponsor.key().as_ref()],
+        bump = paymaster.bump,
+        has_one = sponsor,
+        realloc = paymaster.to_account_info().data_len() + SponsoredAccount::SIZE,
+        realloc::payer = sponsor,
+        realloc::zero = false,
+    )]
+    pub paymaster: Account<'info, Paymaster>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveSponsoredAccount<'info> {
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [PAYMASTER_SEED, sponsor.key().as_ref()],
+        bump = paymaster.bump,
+        has_one = sponsor,
+        realloc = paymaster.to_account_info().data_len() - SponsoredAccount::SIZE,
+        realloc::payer = sponsor,
+        realloc::zero = false,
+    )]
+    pub paymaster: Account<'info, Paymaster>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFromPaymaster<'info> {
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [PAYMASTER_SEED, sponsor.key().as_ref()],
+        bump = paymaster.bump,
+        has_one = sponsor,
+    )]
+    pub paymaster: Account<'info, Paymaster>,
+}
+
+#[derive(Accounts)]
+pub struct ClosePaymaster<'info> {
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [PAYMASTER_SEED, sponsor.key().as_ref()],
+        bump = paymaster.bump,
+        has_one = sponsor,
+        close = sponsor,
+    )]
+    pub paymaster: Account<'info, Paymaster>,
+}
+
+pub fn init_paymaster_impl(
+    ctx: Context<InitPaymaster>,
+    reimbursement: u64,
+    daily_budget: u64,
+    allowed_actions: u16,
+) -> Result<()> {
+    let paymaster = &mut ctx.accounts.paymaster;
+    paymaster.sponsor = ctx.accounts.sponsor.key();
+    paymaster.reimbursement = reimbursement;
+    paymaster.daily_budget = daily_budget;
+    paymaster.allowed_actions = allowed_actions;
+    paymaster.accounts = vec![];
+    paymaster.bump = ctx.bumps.paymaster;
+
+    Ok(())
+}
+
+/// Replaces the rules; per-account spend already counted for today stands.
+pub fn update_paymaster_impl(
+    ctx: Context<UpdatePaymaster>,
+    reimbursement: u64,
+    daily_budget: u64,
+    allowed_actions: u16,
+) -> Result<()> {
+    let paymaster = &mut ctx.accounts.paymaster;
+    paymaster.reimbursement = reimbursement;
+    paymaster.daily_budget = daily_budget;
+    paymaster.allowed_actions = allowed_actions;
+
+    Ok(())
+}
+
+pub fn add_sponsored_account_impl(
+    ctx: Context<AddSponsoredAccount>,
+    account_id: AccountId,
+) -> Result<()> {
+    let paymaster = &mut ctx.accounts.paymaster;
+
+    require!(
+        paymaster.find(account_id).is_none(),
+        ErrorCode::AccountAlreadySponsored
+    );
+    require!(
+        paymaster.accounts.len() < Paymaster::MAX_ACCOUNTS,
+        ErrorCode::TooManySponsoredAccounts
+    );
+
+    paymaster.accounts.push(SponsoredAccount {
+        account_id,
+        day: 0,
+        spent: 0,
+    });
+
+    Ok(())
+}
+
+pub fn remove_sponsored_account_impl(
+    ctx: Context<RemoveSponsoredAccount>,
+    account_id: AccountId,
+) -> Result<()> {
+    let paymaster = &mut ctx.accounts.paymaster;
+
+    require!(
+        paymaster.find(account_id).is_some(),
+        ErrorCode::AccountNotSponsored
+    );
+    paymaster
+        .accounts
+        .retain(|entry| entry.account_id != account_id);
+
+    Ok(())
+}
+
+pub fn withdraw_from_paymaster_impl(
+    ctx: Context<WithdrawFromPaymaster>,
+    lamports: u64,
+) -> Result<()> {
+    pay_from_paymaster(
+        &ctx.accounts.paymaster,
+        &ctx.accounts.sponsor.to_account_info(),
+        lamports,
+    )
+}
+
+/// Pays the relayer of a sponsored `execute_*` transaction, charging the
+/// account's budget for the day. `action_mask` is the `Action::mask` of the
+/// transaction's action.
+pub(crate) fn reimburse_relayer<'info>(
+    paymaster: &mut Account<'info, Paymaster>,
+    relayer_info: &AccountInfo<'info>,
+    account_id: AccountId,
+    action_mask: u16,
+) -> Result<()> {
+    require!(
+        paymaster.allowed_actions & action_mask != 0,
+        ErrorCode::ActionNotSponsored
+    );
+
+    let reimbursement = paymaster.reimbursement;
+    let daily_budget = paymaster.daily_budget;
+    let day = u64::try_from(Clock::get()?.unix_timestamp / SECONDS_PER_DAY).unwrap_or(0);
+
+    let entry = paymaster
+        .accounts
+        .iter_mut()
+        .find(|entry| entry.account_id == account_id)
+        .ok_or(ErrorCode::AccountNotSponsored)?;
+    if entry.day != day {
+        entry.day = day;
+        entry.spent = 0;
+    }
+    let spent = entry
+        .spent
+        .checked_add(reimbursement)
+        .filter(|spent| *spent <= daily_budget)
+        .ok_or(ErrorCode::DailyBudgetExceeded)?;
+    entry.spent = spent;
+
+    pay_from_paymaster(paymaster, relayer_info, reimbursement)
+}
+
+/// Moves lamports out of the paymaster, never below its rent-exempt minimum.
+fn pay_from_paymaster<'info>(
+    paymaster: &Account<'info, Paymaster>,
+    recipient: &AccountInfo<'info>,
+    lamports: u64,
+) -> Result<()> {
+    let paymaster_info = paymaster.to_account_info();
+    let minimum_balance = Rent::get()?.minimum_balance(paymaster_info.data_len());
+    let remaining = paymaster_info
+        .lamports()
+        .checked_sub(lamports)
+        .filter(|remaining| *remaining >= minimum_balance)
+        .ok_or(ErrorCode::InsufficientPaymasterFunds)?;
+
+    **paymaster_info.try_borrow_mut_lamports()? = remaining;
+    **recipient.try_borrow_mut_lamports()? += lamports;
+
+    Ok(())
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Account is already sponsored by this paymaster")]
+    AccountAlreadySponsored,
+    #[msg("Paymaster sponsors too many accounts")]
+    TooManySponsoredAccounts,
+    #[msg("Account is not sponsored by this paymaster")]
+    AccountNotSponsored,
+    #[msg("Paymaster does not sponsor this action")]
+    ActionNotSponsored,
+    #[msg("Account has exhausted its daily paymaster budget")]
+    DailyBudgetExceeded,
+    #[msg("Paymaster does not hold enough lamports")]
+    InsufficientPaymasterFunds,
+}
@@ -1,22 +1,44 @@
 use crate::{
-    contract::auth::{
-        ek256::get_ek256_data_impl,
-        secp256r1_sha256::get_secp256r1_sha256_data_impl,
-        zk_oidc::{transaction_nonce_hex, verify_zk_oidc_proof, Sp1Groth16Proof},
+    contract::{
+        auth::{
+            ek256::get_ek256_data_impl,
+            secp256r1_sha256::get_secp256r1_sha256_data_impl,
+            zk_oidc::{
+                check_oidc_registry, transaction_nonce_hex, verify_zk_oidc_aggregated_proof,
+                verify_zk_oidc_proof, Sp1Groth16Proof, VerifiedJwt,
+            },
+        },
+        oidc_registry::{
+            accept_oidc_authorities_as_account, add_oidc_key_as_account,
+            propose_oidc_authorities_as_account, remove_oidc_key_as_account,
+            set_oidc_clock_skew_as_account, set_oidc_key_add_delay_as_account,
+        },
+        paymaster::reimburse_relayer,
+        transaction_buffer::UnifiedStorage,
+    },
+    pda_seeds::{
+        ABSTRACT_ACCOUNT_SEED, ACCOUNT_MANAGER_SEED, OIDC_KEY_REGISTRY_SEED, PAYMASTER_SEED,
+        UNIFIED_STORAGE_SEED, ZK_VKEY_REGISTRY_SEED,
     },
-    pda_seeds::{ABSTRACT_ACCOUNT_SEED, ACCOUNT_MANAGER_SEED, OIDC_KEY_REGISTRY_SEED},
     types::{
         account::{AbstractAccount, AbstractAccountOperationAccounts, AccountId},
         account_manager::AccountManager,
         identity::{
             oidc::OidcIdentity, wallet::WalletType, webauthn::WebAuthnAuthenticator, Identity,
         },
+        nonce_bitmap::NonceBitmap,
         oidc_key_registry::OidcKeyRegistry,
-        transaction::transaction::{Action, SignRequest, Transaction, WebAuthnAuthData},
+        paymaster::Paymaster,
+        transaction::transaction::{
+            Action, RemoveAccountRequest, SignRequest, Transaction, TransactionFee,
+            WebAuthnAuthData,
+        },
+        zk_vkey_registry::ZkVkeyRegistry,
     },
 };
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program;
+use anchor_lang::solana_program::keccak;
 use anchor_lang::solana_program::program::invoke_signed;
 use base64::Engine;
 
@@ -47,6 +69,35 @@ pub struct ExecuteEk256<'info> {
     /// CHECK: Instructions sysvar, verified by address
     #[account(address = solana_program::sysvar::instructions::id())]
     pub instructions: AccountInfo<'info>,
+
+    /// Reimburses the signer when passed; see `Paymaster`.
+    #[account(
+        mut,
+        seeds = [PAYMASTER_SEED, paymaster.sponsor.as_ref()],
+        bump = paymaster.bump,
+    )]
+    pub paymaster: Option<Account<'info, Paymaster>>,
+
+    /// Required by `ReplayProtection::Unordered` transactions.
+    #[account(
+        mut,
+        constraint = nonce_bitmap.account_id == account_id @ ErrorCode::NonceBitmapMismatch,
+    )]
+    pub nonce_bitmap: Option<Account<'info, NonceBitmap>>,
+
+    /// CHECK: Refunded the bitmap's rent when a `RemoveAccount` closes it; must
+    /// be the payer the bitmap recorded.
+    #[account(
+        mut,
+        constraint = nonce_bitmap.as_ref().map(|bitmap| bitmap.payer)
+            == Some(nonce_bitmap_payer.key()) @ ErrorCode::NonceBitmapPayerMismatch,
+    )]
+    pub nonce_bitmap_payer: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Receives the transaction's fee; checked against the `recipient`
+    /// it signed for. Not needed when that recipient is the signer.
+    #[account(mut)]
+    pub fee_recipient: Option<UncheckedAccount<'info>>,
 }
 
 pub fn execute_ek256_impl<'info>(
@@ -56,16 +107,126 @@ pub fn execute_ek256_impl<'info>(
     let (eth_address, signed_message) = get_ek256_data_impl(&ctx.accounts.instructions)?;
 
     let transaction = Transaction::try_from_slice(&signed_message)?;
-    let identity = Identity::Wallet(WalletType::Ethereum(
-        eth_address
-            .try_into()
-            .map_err(|_| ErrorCode::InvalidEthereumAddress)?,
-    ));
-    let abstract_account = &mut ctx.accounts.abstract_account;
+    let identity = ethereum_identity(eth_address)?;
 
-    is_transaction_authorized(abstract_account, account_id, &identity, &transaction)?;
+    authorize_and_dispatch(
+        AbstractAccountOperationAccounts {
+            abstract_account: &mut ctx.accounts.abstract_account,
+            signer_info: ctx.accounts.signer.to_account_info(),
+            system_program_info: ctx.accounts.system_program.to_account_info(),
+        },
+        account_id,
+        ctx.accounts.account_manager.chain_signatures_program_id,
+        ctx.remaining_accounts,
+        ctx.accounts.paymaster.as_mut(),
+        ctx.accounts.nonce_bitmap.as_mut(),
+        ctx.accounts.nonce_bitmap_payer.as_deref(),
+        ctx.accounts.fee_recipient.as_deref(),
+        std::slice::from_ref(&identity),
+        transaction,
+    )
+}
 
-    dispatch_action(
+/// `ExecuteEk256` with the transaction read from a finalized buffer. Anyone may
+/// submit it, since the payload carries its own authorization; the buffer's
+/// rent goes back to its owner.
+/// A transaction too large for the buffer to be needed is also too large for
+/// the secp256k1 instruction, so that instruction signs `keccak256(transaction)`
+/// instead of the transaction itself.
+#[derive(Accounts)]
+#[instruction(account_id: AccountId)]
+pub struct ExecuteEk256FromBuffer<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ABSTRACT_ACCOUNT_SEED, account_id.to_le_bytes().as_ref()],
+        bump = abstract_account.bump,
+    )]
+    pub abstract_account: Account<'info, AbstractAccount>,
+
+    #[account(
+        seeds = [ACCOUNT_MANAGER_SEED],
+        bump = account_manager.bump,
+    )]
+    pub account_manager: Account<'info, AccountManager>,
+
+    /// Holds the Borsh `Transaction`; closed to its owner, who paid for it,
+    /// once the transaction executes.
+    #[account(
+        mut,
+        seeds = [
+            UNIFIED_STORAGE_SEED,
+            unified_storage.load()?.owner.as_ref(),
+            &unified_storage.load()?.data_id,
+        ],
+        bump,
+        close = owner,
+    )]
+    pub unified_storage: AccountLoader<'info, UnifiedStorage>,
+
+    /// CHECK: Receives the buffer's rent; must be its recorded owner.
+    #[account(
+        mut,
+        constraint = unified_storage.load()?.owner == owner.key() @ ErrorCode::BufferOwnerMismatch,
+    )]
+    pub owner: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Instructions sysvar, verified by address
+    #[account(address = solana_program::sysvar::instructions::id())]
+    pub instructions: AccountInfo<'info>,
+
+    /// Reimburses the signer when passed; see `Paymaster`.
+    #[account(
+        mut,
+        seeds = [PAYMASTER_SEED, paymaster.sponsor.as_ref()],
+        bump = paymaster.bump,
+    )]
+    pub paymaster: Option<Account<'info, Paymaster>>,
+
+    /// Required by `ReplayProtection::Unordered` transactions.
+    #[account(
+        mut,
+        constraint = nonce_bitmap.account_id == account_id @ ErrorCode::NonceBitmapMismatch,
+    )]
+    pub nonce_bitmap: Option<Account<'info, NonceBitmap>>,
+
+    /// CHECK: Refunded the bitmap's rent when a `RemoveAccount` closes it; must
+    /// be the payer the bitmap recorded.
+    #[account(
+        mut,
+        constraint = nonce_bitmap.as_ref().map(|bitmap| bitmap.payer)
+            == Some(nonce_bitmap_payer.key()) @ ErrorCode::NonceBitmapPayerMismatch,
+    )]
+    pub nonce_bitmap_payer: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Receives the transaction's fee; checked against the `recipient`
+    /// it signed for. Not needed when that recipient is the signer.
+    #[account(mut)]
+    pub fee_recipient: Option<UncheckedAccount<'info>>,
+}
+
+pub fn execute_ek256_from_buffer_impl<'info>(
+    ctx: Context<'_, '_, '_, 'info, ExecuteEk256FromBuffer<'info>>,
+    account_id: AccountId,
+) -> Result<()> {
+    let (eth_address, signed_message) = get_ek256_data_impl(&ctx.accounts.instructions)?;
+    let identity = ethereum_identity(eth_address)?;
+
+    let transaction = {
+        let data = ctx.accounts.unified_storage.as_ref().try_borrow_data()?;
+        let payload = UnifiedStorage::payload(&data)?;
+        require!(
+            signed_message == keccak::hash(payload).to_bytes(),
+            ErrorCode::TransactionDigestMismatch
+        );
+        Transaction::try_from_slice(payload)?
+    };
+
+    authorize_and_dispatch(
         AbstractAccountOperationAccounts {
             abstract_account: &mut ctx.accounts.abstract_account,
             signer_info: ctx.accounts.signer.to_account_info(),
@@ -74,10 +235,23 @@ pub fn execute_ek256_impl<'info>(
         account_id,
         ctx.accounts.account_manager.chain_signatures_program_id,
         ctx.remaining_accounts,
-        transaction.action,
+        ctx.accounts.paymaster.as_mut(),
+        ctx.accounts.nonce_bitmap.as_mut(),
+        ctx.accounts.nonce_bitmap_payer.as_deref(),
+        ctx.accounts.fee_recipient.as_deref(),
+        std::slice::from_ref(&identity),
+        transaction,
     )
 }
 
+fn ethereum_identity(eth_address: Vec<u8>) -> Result<Identity> {
+    Ok(Identity::Wallet(WalletType::Ethereum(
+        eth_address
+            .try_into()
+            .map_err(|_| ErrorCode::InvalidEthereumAddress)?,
+    )))
+}
+
 #[derive(Accounts)]
 #[instruction(account_id: AccountId)]
 pub struct ExecuteZkOidc<'info> {
@@ -97,13 +271,50 @@ pub struct ExecuteZkOidc<'info> {
     )]
     pub account_manager: Account<'info, AccountManager>,
 
+    /// Registry of the issuer the proof claims; checked against the proven
+    /// `iss` in the handler, since the client picks which PDA to pass.
     #[account(
-        seeds = [OIDC_KEY_REGISTRY_SEED],
+        seeds = [OIDC_KEY_REGISTRY_SEED, oidc_key_registry.iss_hash.as_ref()],
         bump = oidc_key_registry.bump,
     )]
     pub oidc_key_registry: Account<'info, OidcKeyRegistry>,
 
+    #[account(
+        seeds = [ZK_VKEY_REGISTRY_SEED],
+        bump = zk_vkey_registry.bump,
+    )]
+    pub zk_vkey_registry: Account<'info, ZkVkeyRegistry>,
+
     pub system_program: Program<'info, System>,
+
+    /// Reimburses the signer when passed; see `Paymaster`.
+    #[account(
+        mut,
+        seeds = [PAYMASTER_SEED, paymaster.sponsor.as_ref()],
+        bump = paymaster.bump,
+    )]
+    pub paymaster: Option<Account<'info, Paymaster>>,
+
+    /// Required by `ReplayProtection::Unordered` transactions.
+    #[account(
+        mut,
+        constraint = nonce_bitmap.account_id == account_id @ ErrorCode::NonceBitmapMismatch,
+    )]
+    pub nonce_bitmap: Option<Account<'info, NonceBitmap>>,
+
+    /// CHECK: Refunded the bitmap's rent when a `RemoveAccount` closes it; must
+    /// be the payer the bitmap recorded.
+    #[account(
+        mut,
+        constraint = nonce_bitmap.as_ref().map(|bitmap| bitmap.payer)
+            == Some(nonce_bitmap_payer.key()) @ ErrorCode::NonceBitmapPayerMismatch,
+    )]
+    pub nonce_bitmap_payer: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Receives the transaction's fee; checked against the `recipient`
+    /// it signed for. Not needed when that recipient is the signer.
+    #[account(mut)]
+    pub fee_recipient: Option<UncheckedAccount<'info>>,
 }
 
 pub fn execute_zk_oidc_impl<'info>(
@@ -112,36 +323,306 @@ pub fn execute_zk_oidc_impl<'info>(
     transaction: Transaction,
     groth16_proof: Sp1Groth16Proof,
 ) -> Result<()> {
-    let jwt = verify_zk_oidc_proof(&groth16_proof)?;
+    let identity = zk_oidc_identity(
+        &ctx.accounts.oidc_key_registry,
+        &ctx.accounts.zk_vkey_registry,
+        &transaction.try_to_vec()?,
+        &groth16_proof,
+    )?;
 
-    // Proof/transaction binding: the JWT was minted with the hash of this exact
-    // transaction as its nonce claim, so the proof authorizes nothing else.
-    let expected_nonce = transaction_nonce_hex(&transaction.try_to_vec()?);
-    require!(
-        jwt.nonce == expected_nonce,
-        ErrorCode::TransactionBindingMismatch
-    );
+    authorize_and_dispatch(
+        AbstractAccountOperationAccounts {
+            abstract_account: &mut ctx.accounts.abstract_account,
+            signer_info: ctx.accounts.signer.to_account_info(),
+            system_program_info: ctx.accounts.system_program.to_account_info(),
+        },
+        account_id,
+        ctx.accounts.account_manager.chain_signatures_program_id,
+        ctx.remaining_accounts,
+        ctx.accounts.paymaster.as_mut(),
+        ctx.accounts.nonce_bitmap.as_mut(),
+        ctx.accounts.nonce_bitmap_payer.as_deref(),
+        ctx.accounts.fee_recipient.as_deref(),
+        std::slice::from_ref(&identity),
+        transaction,
+    )
+}
+
+/// `ExecuteZkOidc` with the arguments read from a finalized buffer, which
+/// anyone may submit, for proofs that do not fit in one Solana transaction
+/// next to the action.
+#[derive(Accounts)]
+#[instruction(account_id: AccountId)]
+pub struct ExecuteZkOidcFromBuffer<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ABSTRACT_ACCOUNT_SEED, account_id.to_le_bytes().as_ref()],
+        bump = abstract_account.bump,
+    )]
+    pub abstract_account: Account<'info, AbstractAccount>,
+
+    #[account(
+        seeds = [ACCOUNT_MANAGER_SEED],
+        bump = account_manager.bump,
+    )]
+    pub account_manager: Account<'info, AccountManager>,
+
+    /// Registry of the issuer the proof claims; checked against the proven
+    /// `iss` in the handler, since the client picks which PDA to pass.
+    #[account(
+        seeds = [OIDC_KEY_REGISTRY_SEED, oidc_key_registry.iss_hash.as_ref()],
+        bump = oidc_key_registry.bump,
+    )]
+    pub oidc_key_registry: Account<'info, OidcKeyRegistry>,
+
+    #[account(
+        seeds = [ZK_VKEY_REGISTRY_SEED],
+        bump = zk_vkey_registry.bump,
+    )]
+    pub zk_vkey_registry: Account<'info, ZkVkeyRegistry>,
 
-    // JWKS pinning: only proofs against registry-approved provider keys count.
+    /// Holds the Borsh `Transaction` followed by the Borsh `Sp1Groth16Proof`;
+    /// closed to its owner, who paid for it, once the transaction executes.
+    #[account(
+        mut,
+        seeds = [
+            UNIFIED_STORAGE_SEED,
+            unified_storage.load()?.owner.as_ref(),
+            &unified_storage.load()?.data_id,
+        ],
+        bump,
+        close = owner,
+    )]
+    pub unified_storage: AccountLoader<'info, UnifiedStorage>,
+
+    /// CHECK: Receives the buffer's rent; must be its recorded owner.
+    #[account(
+        mut,
+        constraint = unified_storage.load()?.owner == owner.key() @ ErrorCode::BufferOwnerMismatch,
+    )]
+    pub owner: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Reimburses the signer when passed; see `Paymaster`.
+    #[account(
+        mut,
+        seeds = [PAYMASTER_SEED, paymaster.sponsor.as_ref()],
+        bump = paymaster.bump,
+    )]
+    pub paymaster: Option<Account<'info, Paymaster>>,
+
+    /// Required by `ReplayProtection::Unordered` transactions.
+    #[account(
+        mut,
+        constraint = nonce_bitmap.account_id == account_id @ ErrorCode::NonceBitmapMismatch,
+    )]
+    pub nonce_bitmap: Option<Account<'info, NonceBitmap>>,
+
+    /// CHECK: Refunded the bitmap's rent when a `RemoveAccount` closes it; must
+    /// be the payer the bitmap recorded.
+    #[account(
+        mut,
+        constraint = nonce_bitmap.as_ref().map(|bitmap| bitmap.payer)
+            == Some(nonce_bitmap_payer.key()) @ ErrorCode::NonceBitmapPayerMismatch,
+    )]
+    pub nonce_bitmap_payer: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Receives the transaction's fee; checked against the `recipient`
+    /// it signed for. Not needed when that recipient is the signer.
+    #[account(mut)]
+    pub fee_recipient: Option<UncheckedAccount<'info>>,
+}
+
+pub fn execute_zk_oidc_from_buffer_impl<'info>(
+    ctx: Context<'_, '_, '_, 'info, ExecuteZkOidcFromBuffer<'info>>,
+    account_id: AccountId,
+) -> Result<()> {
+    let (transaction, identity) = {
+        let data = ctx.accounts.unified_storage.as_ref().try_borrow_data()?;
+        let (transaction, transaction_bytes, rest) =
+            split_transaction(UnifiedStorage::payload(&data)?)?;
+        let groth16_proof = Sp1Groth16Proof::try_from_slice(rest)?;
+
+        let identity = zk_oidc_identity(
+            &ctx.accounts.oidc_key_registry,
+            &ctx.accounts.zk_vkey_registry,
+            transaction_bytes,
+            &groth16_proof,
+        )?;
+        (transaction, identity)
+    };
+
+    authorize_and_dispatch(
+        AbstractAccountOperationAccounts {
+            abstract_account: &mut ctx.accounts.abstract_account,
+            signer_info: ctx.accounts.signer.to_account_info(),
+            system_program_info: ctx.accounts.system_program.to_account_info(),
+        },
+        account_id,
+        ctx.accounts.account_manager.chain_signatures_program_id,
+        ctx.remaining_accounts,
+        ctx.accounts.paymaster.as_mut(),
+        ctx.accounts.nonce_bitmap.as_mut(),
+        ctx.accounts.nonce_bitmap_payer.as_deref(),
+        ctx.accounts.fee_recipient.as_deref(),
+        std::slice::from_ref(&identity),
+        transaction,
+    )
+}
+
+/// The identity a single-JWT proof establishes for the Borsh transaction
+/// `transaction_bytes`.
+fn zk_oidc_identity(
+    oidc_key_registry: &OidcKeyRegistry,
+    zk_vkey_registry: &ZkVkeyRegistry,
+    transaction_bytes: &[u8],
+    groth16_proof: &Sp1Groth16Proof,
+) -> Result<Identity> {
+    let clock = Clock::get()?;
+
+    // Guest pinning: only proofs from a currently accepted guest binary count.
+    // Checked before the (expensive) pairing so unknown guests fail fast.
     require!(
-        ctx.accounts.oidc_key_registry.contains(&jwt.iss, &jwt.pk_hash),
-        ErrorCode::OidcKeyNotRegistered
+        zk_vkey_registry.is_active(&groth16_proof.vkey_hash, clock.slot),
+        ErrorCode::ZkVkeyNotAccepted
     );
 
-    let identity = Identity::Oidc(OidcIdentity {
+    let jwt = verify_zk_oidc_proof(groth16_proof)?;
+    let now = u64::try_from(clock.unix_timestamp).unwrap_or(0);
+    let transaction_nonce = transaction_nonce_hex(transaction_bytes);
+
+    oidc_identity(oidc_key_registry, jwt, &transaction_nonce, now)
+}
+
+/// The identity a verified JWT proves, once the issuer's registry accepts it
+/// for this transaction at `now`.
+fn oidc_identity(
+    registry: &OidcKeyRegistry,
+    jwt: VerifiedJwt,
+    transaction_nonce: &str,
+    now: u64,
+) -> Result<Identity> {
+    check_oidc_registry(&jwt, registry, transaction_nonce, now)?;
+
+    Ok(Identity::Oidc(OidcIdentity {
         iss: jwt.iss,
         aud: jwt.aud,
-        email_hash: jwt.email_hash,
-    });
+        subject_commitment: jwt.subject_commitment,
+    }))
+}
 
-    is_transaction_authorized(
-        &mut ctx.accounts.abstract_account,
-        account_id,
-        &identity,
-        &transaction,
-    )?;
+/// `ExecuteZkOidc` for an aggregated proof. The JWTs may come from different
+/// issuers, so their registries are passed as the leading remaining accounts
+/// instead of a fixed account.
+#[derive(Accounts)]
+#[instruction(account_id: AccountId)]
+pub struct ExecuteZkOidcAggregated<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
 
-    dispatch_action(
+    #[account(
+        mut,
+        seeds = [ABSTRACT_ACCOUNT_SEED, account_id.to_le_bytes().as_ref()],
+        bump = abstract_account.bump,
+    )]
+    pub abstract_account: Account<'info, AbstractAccount>,
+
+    #[account(
+        seeds = [ACCOUNT_MANAGER_SEED],
+        bump = account_manager.bump,
+    )]
+    pub account_manager: Account<'info, AccountManager>,
+
+    #[account(
+        seeds = [ZK_VKEY_REGISTRY_SEED],
+        bump = zk_vkey_registry.bump,
+    )]
+    pub zk_vkey_registry: Account<'info, ZkVkeyRegistry>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Reimburses the signer when passed; see `Paymaster`.
+    #[account(
+        mut,
+        seeds = [PAYMASTER_SEED, paymaster.sponsor.as_ref()],
+        bump = paymaster.bump,
+    )]
+    pub paymaster: Option<Account<'info, Paymaster>>,
+
+    /// Required by `ReplayProtection::Unordered` transactions.
+    #[account(
+        mut,
+        constraint = nonce_bitmap.account_id == account_id @ ErrorCode::NonceBitmapMismatch,
+    )]
+    pub nonce_bitmap: Option<Account<'info, NonceBitmap>>,
+
+    /// CHECK: Refunded the bitmap's rent when a `RemoveAccount` closes it; must
+    /// be the payer the bitmap recorded.
+    #[account(
+        mut,
+        constraint = nonce_bitmap.as_ref().map(|bitmap| bitmap.payer)
+            == Some(nonce_bitmap_payer.key()) @ ErrorCode::NonceBitmapPayerMismatch,
+    )]
+    pub nonce_bitmap_payer: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Receives the transaction's fee; checked against the `recipient`
+    /// it signed for. Not needed when that recipient is the signer.
+    #[account(mut)]
+    pub fee_recipient: Option<UncheckedAccount<'info>>,
+}
+
+/// Every JWT of the aggregated proof must pass the `execute_zk_oidc` checks
+/// against its issuer's registry and name a distinct identity of the account;
+/// together they approve the transaction once. `remaining_accounts` starts with
+/// one registry per JWT, in proof order (the same registry repeats for JWTs of
+/// one issuer), followed by whatever the action needs.
+pub fn execute_zk_oidc_aggregated_impl<'info>(
+    ctx: Context<'_, '_, '_, 'info, ExecuteZkOidcAggregated<'info>>,
+    account_id: AccountId,
+    transaction: Transaction,
+    groth16_proof: Sp1Groth16Proof,
+) -> Result<()> {
+    let clock = Clock::get()?;
+
+    require!(
+        ctx.accounts
+            .zk_vkey_registry
+            .is_active(&groth16_proof.vkey_hash, clock.slot),
+        ErrorCode::ZkVkeyNotAccepted
+    );
+
+    let jwts = verify_zk_oidc_aggregated_proof(&groth16_proof)?;
+    let now = u64::try_from(clock.unix_timestamp).unwrap_or(0);
+    let transaction_nonce = transaction_nonce_hex(&transaction.try_to_vec()?);
+
+    require!(
+        ctx.remaining_accounts.len() >= jwts.len(),
+        ErrorCode::InvalidOidcRegistryAccount
+    );
+    let (registry_infos, action_accounts) = ctx.remaining_accounts.split_at(jwts.len());
+
+    let identities = jwts
+        .into_iter()
+        .zip(registry_infos)
+        .map(|(jwt, registry_info)| {
+            // Owner + discriminator identify a registry PDA of this program;
+            // `oidc_identity` then checks it is the one for the JWT's issuer.
+            require_keys_eq!(
+                *registry_info.owner,
+                crate::ID,
+                ErrorCode::InvalidOidcRegistryAccount
+            );
+            let registry =
+                OidcKeyRegistry::try_deserialize(&mut &registry_info.try_borrow_data()?[..])?;
+            oidc_identity(&registry, jwt, &transaction_nonce, now)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    authorize_and_dispatch(
         AbstractAccountOperationAccounts {
             abstract_account: &mut ctx.accounts.abstract_account,
             signer_info: ctx.accounts.signer.to_account_info(),
@@ -149,8 +630,13 @@ pub fn execute_zk_oidc_impl<'info>(
         },
         account_id,
         ctx.accounts.account_manager.chain_signatures_program_id,
-        ctx.remaining_accounts,
-        transaction.action,
+        action_accounts,
+        ctx.accounts.paymaster.as_mut(),
+        ctx.accounts.nonce_bitmap.as_mut(),
+        ctx.accounts.nonce_bitmap_payer.as_deref(),
+        ctx.accounts.fee_recipient.as_deref(),
+        &identities,
+        transaction,
     )
 }
 
@@ -178,6 +664,35 @@ pub struct ExecuteWebauthn<'info> {
     /// CHECK: Instructions sysvar, verified by address
     #[account(address = solana_program::sysvar::instructions::id())]
     pub instructions: AccountInfo<'info>,
+
+    /// Reimburses the signer when passed; see `Paymaster`.
+    #[account(
+        mut,
+        seeds = [PAYMASTER_SEED, paymaster.sponsor.as_ref()],
+        bump = paymaster.bump,
+    )]
+    pub paymaster: Option<Account<'info, Paymaster>>,
+
+    /// Required by `ReplayProtection::Unordered` transactions.
+    #[account(
+        mut,
+        constraint = nonce_bitmap.account_id == account_id @ ErrorCode::NonceBitmapMismatch,
+    )]
+    pub nonce_bitmap: Option<Account<'info, NonceBitmap>>,
+
+    /// CHECK: Refunded the bitmap's rent when a `RemoveAccount` closes it; must
+    /// be the payer the bitmap recorded.
+    #[account(
+        mut,
+        constraint = nonce_bitmap.as_ref().map(|bitmap| bitmap.payer)
+            == Some(nonce_bitmap_payer.key()) @ ErrorCode::NonceBitmapPayerMismatch,
+    )]
+    pub nonce_bitmap_payer: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Receives the transaction's fee; checked against the `recipient`
+    /// it signed for. Not needed when that recipient is the signer.
+    #[account(mut)]
+    pub fee_recipient: Option<UncheckedAccount<'info>>,
 }
 
 pub fn execute_webauthn_impl<'info>(
@@ -186,9 +701,151 @@ pub fn execute_webauthn_impl<'info>(
     transaction: Transaction,
     auth: WebAuthnAuthData,
 ) -> Result<()> {
+    let identity = webauthn_identity(
+        &ctx.accounts.instructions,
+        &transaction.try_to_vec()?,
+        &auth,
+    )?;
+
+    authorize_and_dispatch(
+        AbstractAccountOperationAccounts {
+            abstract_account: &mut ctx.accounts.abstract_account,
+            signer_info: ctx.accounts.signer.to_account_info(),
+            system_program_info: ctx.accounts.system_program.to_account_info(),
+        },
+        account_id,
+        ctx.accounts.account_manager.chain_signatures_program_id,
+        ctx.remaining_accounts,
+        ctx.accounts.paymaster.as_mut(),
+        ctx.accounts.nonce_bitmap.as_mut(),
+        ctx.accounts.nonce_bitmap_payer.as_deref(),
+        ctx.accounts.fee_recipient.as_deref(),
+        std::slice::from_ref(&identity),
+        transaction,
+    )
+}
+
+/// `ExecuteWebauthn` with the arguments read from a finalized buffer, which
+/// anyone may submit, for assertions whose clientDataJSON is too long for one
+/// Solana transaction.
+#[derive(Accounts)]
+#[instruction(account_id: AccountId)]
+pub struct ExecuteWebauthnFromBuffer<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ABSTRACT_ACCOUNT_SEED, account_id.to_le_bytes().as_ref()],
+        bump = abstract_account.bump,
+    )]
+    pub abstract_account: Account<'info, AbstractAccount>,
+
+    #[account(
+        seeds = [ACCOUNT_MANAGER_SEED],
+        bump = account_manager.bump,
+    )]
+    pub account_manager: Account<'info, AccountManager>,
+
+    /// Holds the Borsh `Transaction` followed by the Borsh `WebAuthnAuthData`;
+    /// closed to its owner, who paid for it, once the transaction executes.
+    #[account(
+        mut,
+        seeds = [
+            UNIFIED_STORAGE_SEED,
+            unified_storage.load()?.owner.as_ref(),
+            &unified_storage.load()?.data_id,
+        ],
+        bump,
+        close = owner,
+    )]
+    pub unified_storage: AccountLoader<'info, UnifiedStorage>,
+
+    /// CHECK: Receives the buffer's rent; must be its recorded owner.
+    #[account(
+        mut,
+        constraint = unified_storage.load()?.owner == owner.key() @ ErrorCode::BufferOwnerMismatch,
+    )]
+    pub owner: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Instructions sysvar, verified by address
+    #[account(address = solana_program::sysvar::instructions::id())]
+    pub instructions: AccountInfo<'info>,
+
+    /// Reimburses the signer when passed; see `Paymaster`.
+    #[account(
+        mut,
+        seeds = [PAYMASTER_SEED, paymaster.sponsor.as_ref()],
+        bump = paymaster.bump,
+    )]
+    pub paymaster: Option<Account<'info, Paymaster>>,
+
+    /// Required by `ReplayProtection::Unordered` transactions.
+    #[account(
+        mut,
+        constraint = nonce_bitmap.account_id == account_id @ ErrorCode::NonceBitmapMismatch,
+    )]
+    pub nonce_bitmap: Option<Account<'info, NonceBitmap>>,
+
+    /// CHECK: Refunded the bitmap's rent when a `RemoveAccount` closes it; must
+    /// be the payer the bitmap recorded.
+    #[account(
+        mut,
+        constraint = nonce_bitmap.as_ref().map(|bitmap| bitmap.payer)
+            == Some(nonce_bitmap_payer.key()) @ ErrorCode::NonceBitmapPayerMismatch,
+    )]
+    pub nonce_bitmap_payer: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Receives the transaction's fee; checked against the `recipient`
+    /// it signed for. Not needed when that recipient is the signer.
+    #[account(mut)]
+    pub fee_recipient: Option<UncheckedAccount<'info>>,
+}
+
+pub fn execute_webauthn_from_buffer_impl<'info>(
+    ctx: Context<'_, '_, '_, 'info, ExecuteWebauthnFromBuffer<'info>>,
+    account_id: AccountId,
+) -> Result<()> {
+    let (transaction, identity) = {
+        let data = ctx.accounts.unified_storage.as_ref().try_borrow_data()?;
+        let (transaction, transaction_bytes, rest) =
+            split_transaction(UnifiedStorage::payload(&data)?)?;
+        let auth = WebAuthnAuthData::try_from_slice(rest)?;
+
+        let identity = webauthn_identity(&ctx.accounts.instructions, transaction_bytes, &auth)?;
+        (transaction, identity)
+    };
+
+    authorize_and_dispatch(
+        AbstractAccountOperationAccounts {
+            abstract_account: &mut ctx.accounts.abstract_account,
+            signer_info: ctx.accounts.signer.to_account_info(),
+            system_program_info: ctx.accounts.system_program.to_account_info(),
+        },
+        account_id,
+        ctx.accounts.account_manager.chain_signatures_program_id,
+        ctx.remaining_accounts,
+        ctx.accounts.paymaster.as_mut(),
+        ctx.accounts.nonce_bitmap.as_mut(),
+        ctx.accounts.nonce_bitmap_payer.as_deref(),
+        ctx.accounts.fee_recipient.as_deref(),
+        std::slice::from_ref(&identity),
+        transaction,
+    )
+}
+
+/// The passkey identity a secp256r1-verified WebAuthn assertion over the Borsh
+/// transaction `transaction_bytes` establishes.
+fn webauthn_identity(
+    instructions: &AccountInfo,
+    transaction_bytes: &[u8],
+    auth: &WebAuthnAuthData,
+) -> Result<Identity> {
     use anchor_lang::solana_program::hash::hash as sha256;
 
-    let (pubkey, signed_message) = get_secp256r1_sha256_data_impl(&ctx.accounts.instructions)?;
+    let (pubkey, signed_message) = get_secp256r1_sha256_data_impl(instructions)?;
 
     // 1. The precompile verified a signature over
     //    `authenticator_data || sha256(clientDataJSON)`. Re-bind the raw
@@ -214,7 +871,7 @@ pub fn execute_webauthn_impl<'info>(
     );
 
     // 3. Transaction binding: challenge == base64url(sha256(borsh(transaction))).
-    let tx_hash = sha256(&transaction.try_to_vec()?).to_bytes();
+    let tx_hash = sha256(transaction_bytes).to_bytes();
     let challenge = base64::engine::general_purpose::URL_SAFE_NO_PAD
         .decode(client_data.challenge.as_bytes())
         .map_err(|_| ErrorCode::InvalidClientData)?;
@@ -243,42 +900,93 @@ pub fn execute_webauthn_impl<'info>(
     // 5. Reconstruct the identity. Equality (pubkey + rpIdHash + origin) is what
     //    binds this assertion to a registered credential inside
     //    is_transaction_authorized — a key reused on another origin won't match.
-    let identity = Identity::WebAuthn(WebAuthnAuthenticator {
+    Ok(Identity::WebAuthn(WebAuthnAuthenticator {
         key_id: String::new(),
         compressed_public_key: Some(format!("0x{}", hex::encode(&pubkey))),
         rp_id_hash,
         origin: client_data.origin,
-    });
+    }))
+}
+
+/// Splits a buffer payload into its leading Borsh `Transaction`, the exact bytes
+/// it was decoded from, and the rest. Bindings hash those bytes, so they cover
+/// what the user signed rather than a re-encoding.
+fn split_transaction(payload: &[u8]) -> Result<(Transaction, &[u8], &[u8])> {
+    let mut rest = payload;
+    let transaction = Transaction::deserialize(&mut rest)?;
+    let (transaction_bytes, rest) = payload.split_at(payload.len() - rest.len());
+    Ok((transaction, transaction_bytes, rest))
+}
 
+/// Checks `identities` may together run `transaction` on the account,
+/// consuming its nonce (from `nonce_bitmap` if unordered), has the paymaster
+/// (if any) reimburse the signer, then runs the action. Every `execute_*`
+/// path goes through here, single-identity ones with a one-element slice.
+#[allow(clippy::too_many_arguments)]
+fn authorize_and_dispatch<'info>(
+    mut operation_accounts: AbstractAccountOperationAccounts<'_, 'info>,
+    account_id: AccountId,
+    chain_signatures_program_id: Pubkey,
+    remaining_accounts: &[AccountInfo<'info>],
+    paymaster: Option<&mut Account<'info, Paymaster>>,
+    mut nonce_bitmap: Option<&mut Account<'info, NonceBitmap>>,
+    nonce_bitmap_payer: Option<&AccountInfo<'info>>,
+    fee_recipient: Option<&AccountInfo<'info>>,
+    identities: &[Identity],
+    transaction: Transaction,
+) -> Result<()> {
     is_transaction_authorized(
-        &mut ctx.accounts.abstract_account,
+        &mut operation_accounts,
+        nonce_bitmap.as_deref_mut().map(|bitmap| &mut **bitmap),
         account_id,
-        &identity,
+        identities,
         &transaction,
     )?;
 
+    if let Some(paymaster) = paymaster {
+        reimburse_relayer(
+            paymaster,
+            &operation_accounts.signer_info,
+            account_id,
+            transaction.action.mask(),
+        )?;
+    }
+
     dispatch_action(
-        AbstractAccountOperationAccounts {
-            abstract_account: &mut ctx.accounts.abstract_account,
-            signer_info: ctx.accounts.signer.to_account_info(),
-            system_program_info: ctx.accounts.system_program.to_account_info(),
-        },
+        operation_accounts,
         account_id,
-        ctx.accounts.account_manager.chain_signatures_program_id,
-        ctx.remaining_accounts,
-        transaction.action,
+        chain_signatures_program_id,
+        remaining_accounts,
+        nonce_bitmap.as_deref(),
+        nonce_bitmap_payer,
+        fee_recipient,
+        transaction,
     )
 }
 
+/// Runs the transaction's action, then pays its fee, if any.
+#[allow(clippy::too_many_arguments)]
 fn dispatch_action<'info>(
     operation_accounts: AbstractAccountOperationAccounts<'_, 'info>,
     account_id: AccountId,
     chain_signatures_program_id: Pubkey,
     remaining_accounts: &[AccountInfo<'info>],
-    action: Action,
+    nonce_bitmap: Option<&Account<'info, NonceBitmap>>,
+    nonce_bitmap_payer: Option<&AccountInfo<'info>>,
+    fee_recipient: Option<&AccountInfo<'info>>,
+    transaction: Transaction,
 ) -> Result<()> {
-    match action {
-        Action::RemoveAccount => AbstractAccount::close_account(operation_accounts),
+    let account_info = operation_accounts.abstract_account.to_account_info();
+    let signer_info = operation_accounts.signer_info.clone();
+
+    match transaction.action {
+        Action::RemoveAccount(req) => dispatch_remove_account(
+            operation_accounts,
+            remaining_accounts,
+            nonce_bitmap,
+            nonce_bitmap_payer,
+            req,
+        ),
         Action::AddIdentity(identity_with_permissions) => {
             AbstractAccount::add_identity(operation_accounts, identity_with_permissions)
         }
@@ -292,7 +1000,80 @@ fn dispatch_action<'info>(
             remaining_accounts,
             req,
         ),
+        Action::AddOidcKey(req) => {
+            add_oidc_key_as_account(operation_accounts, remaining_accounts, req)
+        }
+        Action::RemoveOidcKey(req) => {
+            remove_oidc_key_as_account(operation_accounts, remaining_accounts, req)
+        }
+        Action::AcceptOidcAuthorities(req) => {
+            accept_oidc_authorities_as_account(operation_accounts, remaining_accounts, req)
+        }
+        Action::SetRentPayer(rent_payer) => {
+            operation_accounts.abstract_account.rent_payer = rent_payer;
+            Ok(())
+        }
+        Action::ProposeOidcAuthorities(req) => {
+            propose_oidc_authorities_as_account(operation_accounts, remaining_accounts, req)
+        }
+        Action::SetOidcClockSkew(req) => {
+            set_oidc_clock_skew_as_account(operation_accounts, remaining_accounts, req)
+        }
+        Action::SetOidcKeyAddDelay(req) => {
+            set_oidc_key_add_delay_as_account(operation_accounts, remaining_accounts, req)
+        }
+    }?;
+
+    match transaction.fee {
+        Some(fee) => pay_fee(&account_info, &signer_info, fee_recipient, fee),
+        None => Ok(()),
+    }
+}
+
+/// Pays a transaction fee from the account to the `recipient` it names: the
+/// signer, or else the `fee_recipient` account. Remaining accounts are never
+/// searched, so they stay free for the action's own positional layout.
+fn pay_fee<'info>(
+    account_info: &AccountInfo<'info>,
+    signer_info: &AccountInfo<'info>,
+    fee_recipient: Option<&AccountInfo<'info>>,
+    fee: TransactionFee,
+) -> Result<()> {
+    let recipient = std::iter::once(signer_info)
+        .chain(fee_recipient)
+        .find(|info| *info.key == fee.recipient && info.is_writable)
+        .ok_or(ErrorCode::FeeRecipientMissing)?;
+
+    AbstractAccount::pay_from_account(account_info, recipient, fee.lamports)
+}
+
+/// Close the account, refunding its lamports to the `refund_to` the
+/// transaction signed for. `remaining_accounts` must be exactly that account,
+/// writable. A `nonce_bitmap` passed to the instruction is closed too, refunding
+/// the `nonce_bitmap_payer` that funded it.
+fn dispatch_remove_account<'info>(
+    operation_accounts: AbstractAccountOperationAccounts<'_, 'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    nonce_bitmap: Option<&Account<'info, NonceBitmap>>,
+    nonce_bitmap_payer: Option<&AccountInfo<'info>>,
+    req: RemoveAccountRequest,
+) -> Result<()> {
+    let [refund_to] = remaining_accounts else {
+        return Err(ErrorCode::InvalidRefundAccount.into());
+    };
+    require_keys_eq!(
+        *refund_to.key,
+        req.refund_to,
+        ErrorCode::InvalidRefundAccount
+    );
+    require!(refund_to.is_writable, ErrorCode::InvalidRefundAccount);
+
+    if let Some(nonce_bitmap) = nonce_bitmap {
+        let payer = nonce_bitmap_payer.ok_or(ErrorCode::NonceBitmapPayerMismatch)?;
+        nonce_bitmap.close(payer.clone())?;
     }
+
+    AbstractAccount::close_account(operation_accounts, refund_to)
 }
 
 /// Forward a `Sign` action to the deployment-configured chain-signatures
@@ -359,10 +1140,20 @@ fn dispatch_sign<'info>(
 pub enum ErrorCode {
     #[msg("Invalid Ethereum address in verification instruction")]
     InvalidEthereumAddress,
-    #[msg("JWT nonce does not match the transaction hash")]
-    TransactionBindingMismatch,
-    #[msg("OIDC signing key not present in the registry")]
-    OidcKeyNotRegistered,
+    #[msg("Signed message is not the keccak256 digest of the buffered transaction")]
+    TransactionDigestMismatch,
+    #[msg("Owner account does not match the transaction buffer's owner")]
+    BufferOwnerMismatch,
+    #[msg("RemoveAccount expects its writable refund_to account as the only remaining account")]
+    InvalidRefundAccount,
+    #[msg("Nonce bitmap payer account does not match the payer the bitmap recorded")]
+    NonceBitmapPayerMismatch,
+    #[msg("Fee recipient must be the signer or the writable fee_recipient account")]
+    FeeRecipientMissing,
+    #[msg("ZK verification key is not active in the registry")]
+    ZkVkeyNotAccepted,
+    #[msg("Expected one OIDC registry account per aggregated JWT")]
+    InvalidOidcRegistryAccount,
     #[msg("Sign action requires [program_state, event_authority, chain_signatures_program] as remaining accounts")]
     InvalidChainSignaturesAccounts,
     #[msg("Provided chain-signatures program does not match the configured deployment id")]
@@ -379,4 +1170,6 @@ pub enum ErrorCode {
     InvalidAuthenticatorData,
     #[msg("Malformed clientDataJSON")]
     InvalidClientData,
+    #[msg("Nonce bitmap belongs to another account")]
+    NonceBitmapMismatch,
 }
@@ -1,43 +1,100 @@
 use anchor_lang::prelude::*;
 
 use crate::types::{
-    account::{AbstractAccount, AccountId},
+    account::{split_nonce, AbstractAccount, AbstractAccountOperationAccounts, AccountId},
     identity::Identity,
-    transaction::transaction::Transaction,
+    nonce_bitmap::NonceBitmap,
+    transaction::transaction::{Action, ReplayProtection, Transaction},
 };
 
 /// Validates the user operation against the abstract account
 ///
 /// This function performs the following validations:
-/// 1. Checks if the identity in the user operation exists in the abstract account
+/// 1. Checks that every approving identity exists in the abstract account and
+///    approves only once, so a repeated identity cannot pose as a co-signer
 /// 2. Verifies that the nonce in the transaction matches the account's current nonce
-/// 3. Increments the account nonce to prevent replay attacks
+///    on the transaction's nonce key, or for an unordered transaction that it has
+///    not expired
+/// 3. Verifies that the signer is the transaction's relayer, if it pins one
+/// 4. Rejects a fee on `RemoveAccount`, which leaves no account to pay it from
+/// 5. Increments that key's nonce to prevent replay attacks, opening the key on
+///    its first use, or marks the unordered nonce used in `nonce_bitmap`. The
+///    nonce is consumed once for the whole set of identities
 ///
 /// # Arguments
-/// * `abstract_account` - The abstract account to validate against
+/// * `operation_accounts` - The abstract account to validate against, and the
+///   outer Solana signer submitting the transaction
+/// * `nonce_bitmap` - The account's bitmap, required by unordered transactions
+/// * `identities` - The identities approving the transaction, usually just one
 /// * `user_op` - The user operation containing the transaction details
 ///
 /// # Returns
 /// * `Result<()>` - Returns Ok() if validation succeeds, or the error code if validation fails
 pub fn is_transaction_authorized(
-    abstract_account: &mut AbstractAccount,
+    operation_accounts: &mut AbstractAccountOperationAccounts,
+    nonce_bitmap: Option<&mut NonceBitmap>,
     account_id: AccountId,
-    identity: &Identity,
+    identities: &[Identity],
     transaction: &Transaction,
 ) -> Result<()> {
-    if !abstract_account.has_identity(&identity) {
-        return Err(ErrorCode::IdentityNotFound.into());
+    let abstract_account = &operation_accounts.abstract_account;
+
+    require!(!identities.is_empty(), ErrorCode::IdentityNotFound);
+
+    for (index, identity) in identities.iter().enumerate() {
+        if !abstract_account.has_identity(identity) {
+            return Err(ErrorCode::IdentityNotFound.into());
+        }
+        if identities[..index].contains(identity) {
+            return Err(ErrorCode::DuplicateApproval.into());
+        }
     }
 
-    if !abstract_account.nonce.eq(&transaction.nonce) {
-        return Err(ErrorCode::NonceMismatch.into());
+    let (nonce_key, _) = split_nonce(transaction.nonce);
+    match transaction.replay_protection {
+        ReplayProtection::Sequential => {
+            if !abstract_account
+                .expected_nonce(nonce_key)
+                .eq(&transaction.nonce)
+            {
+                return Err(ErrorCode::NonceMismatch.into());
+            }
+        }
+        ReplayProtection::Unordered { expiry_slot } => {
+            if Clock::get()?.slot > expiry_slot {
+                return Err(ErrorCode::TransactionExpired.into());
+            }
+        }
     }
 
     if !account_id.eq(&transaction.account_id) {
         return Err(ErrorCode::AccountIdMismatch.into());
     }
 
-    abstract_account.increment_nonce();
+    if transaction
+        .relayer
+        .is_some_and(|relayer| relayer != *operation_accounts.signer_info.key)
+    {
+        return Err(ErrorCode::RelayerMismatch.into());
+    }
+
+    if matches!(transaction.action, Action::RemoveAccount(_)) && transaction.fee.is_some() {
+        return Err(ErrorCode::FeeOnRemoveAccount.into());
+    }
+
+    match transaction.replay_protection {
+        ReplayProtection::Sequential => {
+            if !abstract_account.has_nonce_key(nonce_key) {
+                AbstractAccount::open_nonce_key(operation_accounts, nonce_key)?;
+            }
+            operation_accounts
+                .abstract_account
+                .increment_nonce(nonce_key);
+        }
+        ReplayProtection::Unordered { .. } => nonce_bitmap
+            .ok_or(ErrorCode::NonceBitmapMissing)?
+            .consume(transaction.nonce)?,
+    }
 
     // TODO: Include verification for act_as
 
@@ -54,4 +111,14 @@ pub enum ErrorCode {
     NonceMismatch,
     #[msg("Account ID mismatch")]
     AccountIdMismatch,
+    #[msg("The same identity approved the transaction more than once")]
+    DuplicateApproval,
+    #[msg("Transaction is pinned to another relayer")]
+    RelayerMismatch,
+    #[msg("Unordered transaction is past its expiry slot")]
+    TransactionExpired,
+    #[msg("Unordered transaction needs the account's nonce bitmap")]
+    NonceBitmapMissing,
+    #[msg("RemoveAccount cannot carry a fee")]
+    FeeOnRemoveAccount,
 }
@@ -1,256 +1,419 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+use anchor_lang::solana_program::hash::hash;
+
+use crate::pda_seeds::UNIFIED_STORAGE_SEED;
 
 /*
-    TODO: 
-    - The current implementation is limited by Solana's heap size (32kb) as the chunks vector needs to be loaded into memory for vector operations. We should consider using zero-copy accounts to avoid this limitation.
-    - The current implementation reallocates memory for each transaction, which is inefficient. We should pre-allocate the required memory based on the total chunks size, enabling parallel transaction processing.
+    Layout of a buffer account: the 8-byte discriminator, the `UnifiedStorage`
+    header, a bitmap of stored chunks (one bit per chunk) and the payload itself.
+    Chunk `i` always lives at payload offset `i * MAX_CHUNK_SIZE`, so chunks are
+    written straight into place, in any order, without touching the heap.
+
+    An instruction can grow an account by at most 10 KiB, so `init_storage`
+    allocates up to that much and `extend_storage` grows the rest in 10 KiB
+    steps. A chunk can be stored as soon as its range is allocated.
+
+    Once every chunk is stored, `finalize_storage` checks the payload against
+    `data_hash` and freezes the buffer. Only finalized buffers are consumed.
+
+    A buffer belongs to the `owner` who signed its creation and lives under the
+    owner's key, whoever pays for it. The owner and up to `MAX_WRITERS` writers
+    named at creation (e.g. a relayer finishing an upload) may grow, fill and
+    finalize it. Rent always goes back to the owner: on close, on execution, or
+    through the permissionless `reclaim_expired_storage` once the buffer is
+    `STORAGE_EXPIRY_SLOTS` old.
 */
 
 const DISCRIMINATOR_SIZE: usize = 8;
-const PUBKEY_SIZE: usize = 32;
-const U16_SIZE: usize = 2;
-const VEC_PREFIX_SIZE: usize = 4;
-const CHUNK_OVERHEAD: usize = U16_SIZE + 1 + VEC_PREFIX_SIZE; // index (u16) + is_stored (bool) + vec prefix
+const HEADER_SIZE: usize = DISCRIMINATOR_SIZE + std::mem::size_of::<UnifiedStorage>();
 const MAX_CHUNK_SIZE: usize = 900;
+/// Largest payload a buffer accepts.
+pub const MAX_DATA_LEN: u32 = 1024 * 1024;
+pub const MAX_WRITERS: usize = 4;
+/// Age after which anyone may close a buffer (~1 day at 400 ms slots).
+pub const STORAGE_EXPIRY_SLOTS: u64 = 216_000;
 
 pub fn init_storage_impl(
-    ctx: Context<InitStorage>, 
-    data_id: [u8; 32], 
-    chunk_index: u16, 
-    total_chunks: u16,
-    data_hash: [u8; 32], 
-    chunk_data: Vec<u8>
+    ctx: Context<InitStorage>,
+    data_id: [u8; 32],
+    writers: Vec<Pubkey>,
+    data_len: u32,
+    data_hash: [u8; 32],
 ) -> Result<()> {
-    msg!("Initializing new storage account");
-    let storage = &mut ctx.accounts.unified_storage;
+    require!(writers.len() <= MAX_WRITERS, ErrorCode::TooManyWriters);
+
+    let mut storage = ctx.accounts.unified_storage.load_init()?;
     storage.data_id = data_id;
-    storage.total_chunks = total_chunks;
     storage.data_hash = data_hash;
-    storage.chunks_stored = 1;
-    storage.chunks = vec![ChunkData {
-        index: chunk_index,
-        data: chunk_data,
-        is_stored: true
-    }];
-    
+    storage.owner = ctx.accounts.owner.key();
+    storage.writers[..writers.len()].copy_from_slice(&writers);
+    storage.writer_count = writers.len() as u8;
+    storage.created_slot = Clock::get()?.slot;
+    storage.data_len = data_len;
+    storage.total_chunks = total_chunks(data_len);
+    storage.chunks_stored = 0;
+    storage.finalized = 0;
+
+    msg!(
+        "Initialized storage for {} bytes in {} chunks",
+        data_len,
+        storage.total_chunks
+    );
+    Ok(())
+}
+
+pub fn extend_storage_impl(ctx: Context<ExtendStorage>) -> Result<()> {
+    msg!(
+        "Storage allocated {} bytes",
+        ctx.accounts.unified_storage.as_ref().data_len()
+    );
     Ok(())
 }
 
 pub fn store_chunk_impl(
-    ctx: Context<StoreChunk>, 
-    data_id: [u8; 32], 
-    chunk_index: u16, 
-    total_chunks: u16,
-    data_hash: [u8; 32], 
-    chunk_data: Vec<u8>
+    ctx: Context<StoreChunk>,
+    chunk_index: u16,
+    chunk_data: Vec<u8>,
 ) -> Result<()> {
-    require!(chunk_data.len() <= MAX_CHUNK_SIZE, ErrorCode::ChunkTooLarge);
-    
-    let initial = ctx.accounts.unified_storage.chunks.is_empty();
-
-    require!(!initial, ErrorCode::StorageNotInitialized);
-    
-    // Verify data consistency
-    let storage = &mut ctx.accounts.unified_storage;
-    require!(storage.data_id == data_id, ErrorCode::InvalidDataId);
-    require!(storage.total_chunks == total_chunks, ErrorCode::InvalidTotalChunks);
-    require!(storage.data_hash == data_hash, ErrorCode::InvalidDataHash);    
-    require!(chunk_index < total_chunks, ErrorCode::InvalidChunkIndex);
-
-    storage.chunks.push(ChunkData {
-        index: chunk_index,
-        data: chunk_data.clone(),
-        is_stored: true,
-    });
-    
-    storage.chunks_stored = storage.chunks_stored.saturating_add(1);
-    
-    msg!("Stored chunk {}/{} with size {} bytes", 
-        chunk_index + 1, total_chunks, storage.chunks[chunk_index as usize].data.len());
+    let mut data = ctx
+        .accounts
+        .unified_storage
+        .as_ref()
+        .try_borrow_mut_data()?;
+    let (storage, bitmap, payload) = split_mut(&mut data);
+
+    require!(
+        storage.is_writer(&ctx.accounts.writer.key()),
+        ErrorCode::NotStorageWriter
+    );
+    require!(storage.finalized == 0, ErrorCode::StorageFinalized);
+    require!(
+        chunk_index < storage.total_chunks,
+        ErrorCode::InvalidChunkIndex
+    );
+    let range = storage.chunk_range(chunk_index);
+    require!(
+        chunk_data.len() == range.len(),
+        ErrorCode::InvalidChunkLength
+    );
+    require!(payload.len() >= range.end, ErrorCode::ChunkNotAllocated);
+    require!(
+        !is_stored(bitmap, chunk_index),
+        ErrorCode::ChunkAlreadyStored
+    );
+
+    payload[range].copy_from_slice(&chunk_data);
+    bitmap[usize::from(chunk_index / 8)] |= 1 << (chunk_index % 8);
+    storage.chunks_stored += 1;
+
+    msg!(
+        "Stored chunk {}/{} with size {} bytes",
+        chunk_index + 1,
+        storage.total_chunks,
+        chunk_data.len()
+    );
+    Ok(())
+}
+
+pub fn finalize_storage_impl(ctx: Context<FinalizeStorage>) -> Result<()> {
+    let mut data = ctx
+        .accounts
+        .unified_storage
+        .as_ref()
+        .try_borrow_mut_data()?;
+    let (storage, bitmap, payload) = split_mut(&mut data);
+
+    require!(
+        storage.is_writer(&ctx.accounts.writer.key()),
+        ErrorCode::NotStorageWriter
+    );
+    require!(storage.finalized == 0, ErrorCode::StorageFinalized);
+    // The bitmap admits each index once, so every bit set means every chunk
+    // stored exactly once.
+    require!(
+        (0..storage.total_chunks).all(|index| is_stored(bitmap, index)),
+        ErrorCode::StorageIncomplete
+    );
+    require!(
+        hash(&payload[..storage.data_len as usize]).to_bytes() == storage.data_hash,
+        ErrorCode::InvalidDataHash
+    );
+
+    storage.finalized = 1;
+    msg!("Finalized storage of {} bytes", storage.data_len);
     Ok(())
 }
 
 pub fn retrieve_chunk_impl(ctx: Context<RetrieveChunk>, chunk_index: u16) -> Result<Vec<u8>> {
-    let storage = &ctx.accounts.unified_storage;
-    
-    require!(chunk_index < storage.total_chunks, ErrorCode::InvalidChunkIndex);
-    
-    if chunk_index as usize >= storage.chunks.len() {
-        return err!(ErrorCode::ChunkNotAllocated);
-    }
-    
-    require!(storage.chunks[chunk_index as usize].is_stored, ErrorCode::ChunkNotStored);
-    
-    Ok(storage.chunks[chunk_index as usize].data.clone())
+    let data = ctx.accounts.unified_storage.as_ref().try_borrow_data()?;
+    let (storage, bitmap, payload) = split(&data);
+
+    require!(
+        chunk_index < storage.total_chunks,
+        ErrorCode::InvalidChunkIndex
+    );
+    require!(is_stored(bitmap, chunk_index), ErrorCode::ChunkNotStored);
+
+    Ok(payload[storage.chunk_range(chunk_index)].to_vec())
 }
 
 pub fn get_data_metadata_impl(ctx: Context<GetDataMetadata>) -> Result<DataMetadata> {
-    let storage = &ctx.accounts.unified_storage;
-    
+    let storage = ctx.accounts.unified_storage.load()?;
+
     Ok(DataMetadata {
         data_id: storage.data_id,
+        owner: storage.owner,
+        writers: storage.writers[..usize::from(storage.writer_count)].to_vec(),
+        created_slot: storage.created_slot,
+        data_len: storage.data_len,
         total_chunks: storage.total_chunks,
         chunks_stored: storage.chunks_stored,
         data_hash: storage.data_hash,
+        finalized: storage.finalized != 0,
     })
 }
 
 pub fn close_storage_impl(ctx: Context<CloseStorage>) -> Result<()> {
-    msg!("Closed storage account. Freed {} chunks", ctx.accounts.unified_storage.chunks_stored);
-    
+    msg!(
+        "Closed storage account. Freed {} chunks",
+        ctx.accounts.unified_storage.load()?.chunks_stored
+    );
     Ok(())
 }
 
+pub fn reclaim_expired_storage_impl(ctx: Context<ReclaimExpiredStorage>) -> Result<()> {
+    let created_slot = ctx.accounts.unified_storage.load()?.created_slot;
+    require!(
+        Clock::get()?.slot >= created_slot.saturating_add(STORAGE_EXPIRY_SLOTS),
+        ErrorCode::StorageNotExpired
+    );
+
+    msg!("Reclaimed storage created at slot {}", created_slot);
+    Ok(())
+}
 
 #[derive(Accounts)]
-#[instruction(data_id: [u8; 32], chunk_index: u16, total_chunks: u16, data_hash: [u8; 32], chunk_data: Vec<u8>)]
+#[instruction(data_id: [u8; 32], writers: Vec<Pubkey>, data_len: u32)]
 pub struct InitStorage<'info> {
     #[account(
-        init_if_needed,
+        init,
         payer = payer,
-        space = calculate_initial_space(),
-        seeds = [
-            b"unified_storage", 
-            payer.key().as_ref(),
-            &data_id
-        ],
+        space = initial_space(data_len)?,
+        seeds = [UNIFIED_STORAGE_SEED, owner.key().as_ref(), &data_id],
         bump
     )]
-    pub unified_storage: Account<'info, UnifiedStorage>,
-    
+    pub unified_storage: AccountLoader<'info, UnifiedStorage>,
+
+    pub owner: Signer<'info>,
     #[account(mut)]
     pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(data_id: [u8; 32], chunk_index: u16, total_chunks: u16, data_hash: [u8; 32], chunk_data: Vec<u8>)]
-pub struct StoreChunk<'info> {
+pub struct ExtendStorage<'info> {
     #[account(
         mut,
-        seeds = [
-            b"unified_storage", 
-            payer.key().as_ref(),
-            &data_id
-        ],
-        bump,
-        // TODO: Instead of realloc one by one, we should batch the reallocations
-        realloc = unified_storage.to_account_info().data_len() + CHUNK_OVERHEAD + MAX_CHUNK_SIZE,
-        realloc::payer = payer,
+        constraint = unified_storage.load()?.is_writer(&writer.key()) @ ErrorCode::NotStorageWriter,
+        realloc = next_space(&unified_storage.load()?, unified_storage.as_ref().data_len()),
+        realloc::payer = writer,
         realloc::zero = false
     )]
-    pub unified_storage: Account<'info, UnifiedStorage>,
-    
+    pub unified_storage: AccountLoader<'info, UnifiedStorage>,
+
     #[account(mut)]
-    pub payer: Signer<'info>,
+    pub writer: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
-fn calculate_initial_space() -> usize {
-    // Base size + space for initial chunks
-    DISCRIMINATOR_SIZE + 
-    PUBKEY_SIZE +          // data_id
-    U16_SIZE +             // total_chunks
-    U16_SIZE +             // chunks_stored
-    PUBKEY_SIZE +          // data_hash
-    VEC_PREFIX_SIZE +      // chunks vector prefix
-    U16_SIZE +             // capacity
-    (CHUNK_OVERHEAD + MAX_CHUNK_SIZE) // initial chunks
+#[derive(Accounts)]
+pub struct StoreChunk<'info> {
+    #[account(mut)]
+    pub unified_storage: AccountLoader<'info, UnifiedStorage>,
+    pub writer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeStorage<'info> {
+    #[account(mut)]
+    pub unified_storage: AccountLoader<'info, UnifiedStorage>,
+    pub writer: Signer<'info>,
 }
 
 #[derive(Accounts)]
 #[instruction(chunk_index: u16)]
 pub struct RetrieveChunk<'info> {
-    #[account(
-        seeds = [
-            b"unified_storage", 
-            payer.key().as_ref(),
-            &unified_storage.data_id
-        ],
-        bump
-    )]
-    pub unified_storage: Account<'info, UnifiedStorage>,
-    pub payer: Signer<'info>,
+    pub unified_storage: AccountLoader<'info, UnifiedStorage>,
 }
 
 #[derive(Accounts)]
 pub struct GetDataMetadata<'info> {
+    pub unified_storage: AccountLoader<'info, UnifiedStorage>,
+}
+
+#[derive(Accounts)]
+pub struct CloseStorage<'info> {
     #[account(
-        seeds = [
-            b"unified_storage", 
-            payer.key().as_ref(),
-            &unified_storage.data_id
-        ],
-        bump
+        mut,
+        constraint = unified_storage.load()?.owner == owner.key() @ ErrorCode::NotStorageOwner,
+        close = owner
     )]
-    pub unified_storage: Account<'info, UnifiedStorage>,
-    pub payer: Signer<'info>,
+    pub unified_storage: AccountLoader<'info, UnifiedStorage>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
 }
 
+/// Permissionless: anyone may return an expired buffer's rent to its owner.
 #[derive(Accounts)]
-pub struct CloseStorage<'info> {
+pub struct ReclaimExpiredStorage<'info> {
     #[account(
         mut,
-        seeds = [
-            b"unified_storage", 
-            payer.key().as_ref(),
-            &unified_storage.data_id
-        ],
-        bump,
-        close = payer
+        constraint = unified_storage.load()?.owner == owner.key() @ ErrorCode::NotStorageOwner,
+        close = owner
     )]
-    pub unified_storage: Account<'info, UnifiedStorage>,
+    pub unified_storage: AccountLoader<'info, UnifiedStorage>,
+    /// CHECK: Receives the rent; must be the buffer's recorded owner.
     #[account(mut)]
-    pub payer: Signer<'info>,
+    pub owner: UncheckedAccount<'info>,
 }
 
-#[account]
+/// Header of a buffer account; the chunk bitmap and payload follow it.
+#[account(zero_copy)]
 pub struct UnifiedStorage {
-    pub data_id: [u8; 32],       // Unique identifier for this dataset
-    pub total_chunks: u16,       // Total number of chunks in this dataset
-    pub chunks_stored: u16,      // Number of chunks stored so far
-    pub data_hash: [u8; 32],     // Hash of the entire dataset for integrity verification
-    pub chunks: Vec<ChunkData>,  // Vector of all chunks
+    pub data_id: [u8; 32],              // Unique identifier for this dataset
+    pub data_hash: [u8; 32],            // Hash of the entire dataset for integrity verification
+    pub owner: Pubkey,                  // Receives the rent back; the only one who may use it
+    pub writers: [Pubkey; MAX_WRITERS], // Besides the owner, may fill the buffer
+    pub created_slot: u64,              // Slot of `init_storage`, for expiry
+    pub data_len: u32,                  // Payload size in bytes
+    pub total_chunks: u16,              // Total number of chunks in this dataset
+    pub chunks_stored: u16,             // Number of chunks stored so far
+    pub writer_count: u8,               // Number of `writers` in use
+    pub finalized: u8,                  // Set once the payload is verified; no writes after
+    pub _padding: [u8; 6],
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
-pub struct ChunkData {
-    pub index: u16,              // Index of this chunk (0-based)
-    pub is_stored: bool,         // Whether this chunk has been stored
-    pub data: Vec<u8>,           // Chunk data
+impl UnifiedStorage {
+    /// The payload within `data`, the buffer account's data, once finalized.
+    pub fn payload(data: &[u8]) -> Result<&[u8]> {
+        let (storage, _, payload) = split(data);
+        require!(storage.finalized != 0, ErrorCode::StorageNotFinalized);
+        Ok(&payload[..storage.data_len as usize])
+    }
+
+    pub fn is_writer(&self, key: &Pubkey) -> bool {
+        self.owner == *key || self.writers[..usize::from(self.writer_count)].contains(key)
+    }
+
+    /// Payload byte range of chunk `index`; only the last chunk is short.
+    fn chunk_range(&self, index: u16) -> std::ops::Range<usize> {
+        let start = usize::from(index) * MAX_CHUNK_SIZE;
+        start..(start + MAX_CHUNK_SIZE).min(self.data_len as usize)
+    }
+
+    fn space(&self) -> usize {
+        HEADER_SIZE + bitmap_len(self.total_chunks) + self.data_len as usize
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct DataMetadata {
     pub data_id: [u8; 32],
+    pub owner: Pubkey,
+    pub writers: Vec<Pubkey>,
+    pub created_slot: u64,
+    pub data_len: u32,
     pub total_chunks: u16,
     pub chunks_stored: u16,
     pub data_hash: [u8; 32],
+    pub finalized: bool,
+}
+
+fn total_chunks(data_len: u32) -> u16 {
+    // `MAX_DATA_LEN` keeps this well inside u16.
+    (data_len as usize).div_ceil(MAX_CHUNK_SIZE) as u16
+}
+
+fn bitmap_len(total_chunks: u16) -> usize {
+    usize::from(total_chunks).div_ceil(8)
+}
+
+/// Space `init_storage` allocates: everything, or as much as one instruction
+/// may allocate.
+fn initial_space(data_len: u32) -> Result<usize> {
+    require!(
+        data_len > 0 && data_len <= MAX_DATA_LEN,
+        ErrorCode::InvalidDataLength
+    );
+    let space = HEADER_SIZE + bitmap_len(total_chunks(data_len)) + data_len as usize;
+    Ok(space.min(MAX_PERMITTED_DATA_INCREASE))
+}
+
+/// Space after one more `extend_storage` step from `current`.
+fn next_space(storage: &UnifiedStorage, current: usize) -> usize {
+    (current + MAX_PERMITTED_DATA_INCREASE).min(storage.space())
+}
+
+fn split(data: &[u8]) -> (&UnifiedStorage, &[u8], &[u8]) {
+    let (header, rest) = data.split_at(HEADER_SIZE);
+    let storage: &UnifiedStorage = bytemuck::from_bytes(&header[DISCRIMINATOR_SIZE..]);
+    let (bitmap, payload) = rest.split_at(bitmap_len(storage.total_chunks));
+    (storage, bitmap, payload)
+}
+
+fn split_mut(data: &mut [u8]) -> (&mut UnifiedStorage, &mut [u8], &mut [u8]) {
+    let (header, rest) = data.split_at_mut(HEADER_SIZE);
+    let storage: &mut UnifiedStorage = bytemuck::from_bytes_mut(&mut header[DISCRIMINATOR_SIZE..]);
+    let (bitmap, payload) = rest.split_at_mut(bitmap_len(storage.total_chunks));
+    (storage, bitmap, payload)
+}
+
+fn is_stored(bitmap: &[u8], index: u16) -> bool {
+    bitmap[usize::from(index / 8)] & (1 << (index % 8)) != 0
 }
 
 #[error_code]
 pub enum ErrorCode {
     #[msg("Invalid chunk index")]
     InvalidChunkIndex,
-    
+
     #[msg("Chunk not stored")]
     ChunkNotStored,
-    
-    #[msg("Chunk not yet allocated - need more capacity")]
+
+    #[msg("Chunk not yet allocated - extend the storage first")]
     ChunkNotAllocated,
-    
-    #[msg("Invalid data ID")]
-    InvalidDataId,
-    
-    #[msg("Invalid total chunks")]
-    InvalidTotalChunks,
-    
+
+    #[msg("Chunk already stored")]
+    ChunkAlreadyStored,
+
+    #[msg("Chunk length does not match its position in the data")]
+    InvalidChunkLength,
+
+    #[msg("Data length is zero or exceeds the maximum")]
+    InvalidDataLength,
+
     #[msg("Invalid data hash")]
     InvalidDataHash,
-    
-    #[msg("Chunk too large (exceeds maximum size)")]
-    ChunkTooLarge,
-    
-    #[msg("Storage not initialized")]
-    StorageNotInitialized,
+
+    #[msg("Storage is missing chunks")]
+    StorageIncomplete,
+
+    #[msg("Storage is finalized and can no longer be written")]
+    StorageFinalized,
+
+    #[msg("Storage must be finalized before it is used")]
+    StorageNotFinalized,
+
+    #[msg("Too many storage writers")]
+    TooManyWriters,
+
+    #[msg("Signer may not write to this storage")]
+    NotStorageWriter,
+
+    #[msg("Account is not the storage owner")]
+    NotStorageOwner,
+
+    #[msg("Storage has not expired yet")]
+    StorageNotExpired,
 }
@@ -0,0 +1,198 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    pda_seeds::{ACCOUNT_MANAGER_SEED, ZK_VKEY_REGISTRY_SEED},
+    types::{
+        account_manager::AccountManager,
+        zk_vkey_registry::{ZkVkeyEntry, ZkVkeyRegistry},
+    },
+};
+
+// There is no close instruction: a re-created registry could start with a
+// lower `vkey_add_delay` and skip the timelock.
+
+#[derive(Accounts)]
+pub struct InitZkVkeyRegistry<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [ACCOUNT_MANAGER_SEED],
+        bump = account_manager.bump,
+        constraint = account_manager.admin == authority.key() @ ErrorCode::NotAdmin,
+    )]
+    pub account_manager: Account<'info, AccountManager>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = ZkVkeyRegistry::INIT_SIZE,
+        seeds = [ZK_VKEY_REGISTRY_SEED],
+        bump,
+    )]
+    pub zk_vkey_registry: Account<'info, ZkVkeyRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AddZkVkey<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ZK_VKEY_REGISTRY_SEED],
+        bump = zk_vkey_registry.bump,
+        has_one = authority,
+        realloc = zk_vkey_registry.to_account_info().data_len() + ZkVkeyEntry::SIZE,
+        realloc::payer = authority,
+        realloc::zero = false,
+    )]
+    pub zk_vkey_registry: Account<'info, ZkVkeyRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DeprecateZkVkey<'info> {
+    pub authority: Signer<'info>,
+
+    // Deprecation rewrites a fixed-size field in place, so no realloc.
+    #[account(
+        mut,
+        seeds = [ZK_VKEY_REGISTRY_SEED],
+        bump = zk_vkey_registry.bump,
+        has_one = authority,
+    )]
+    pub zk_vkey_registry: Account<'info, ZkVkeyRegistry>,
+}
+
+#[derive(Accounts)]
+pub struct SetZkVkeyAddDelay<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ZK_VKEY_REGISTRY_SEED],
+        bump = zk_vkey_registry.bump,
+        has_one = authority,
+    )]
+    pub zk_vkey_registry: Account<'info, ZkVkeyRegistry>,
+}
+
+pub fn init_zk_vkey_registry_impl(
+    ctx: Context<InitZkVkeyRegistry>,
+    vkey_add_delay: u64,
+) -> Result<()> {
+    require!(
+        vkey_add_delay <= ZkVkeyRegistry::MAX_VKEY_ADD_DELAY,
+        ErrorCode::ZkVkeyAddDelayTooLarge
+    );
+
+    ctx.accounts.zk_vkey_registry.authority = ctx.accounts.authority.key();
+    ctx.accounts.zk_vkey_registry.vkey_add_delay = vkey_add_delay;
+    ctx.accounts.zk_vkey_registry.vkeys = vec![];
+    ctx.accounts.zk_vkey_registry.bump = ctx.bumps.zk_vkey_registry;
+
+    Ok(())
+}
+
+pub fn add_zk_vkey_impl(ctx: Context<AddZkVkey>, mut vkey_entry: ZkVkeyEntry) -> Result<()> {
+    let registry = &mut ctx.accounts.zk_vkey_registry;
+
+    require!(
+        registry.find(&vkey_entry.vkey_hash).is_none(),
+        ErrorCode::ZkVkeyAlreadyRegistered
+    );
+
+    // Timelock: the vkey sits in the registry, inert, for at least
+    // `vkey_add_delay` slots before any proof from its guest is accepted.
+    let slot = Clock::get()?.slot;
+    vkey_entry.activation_slot = vkey_entry
+        .activation_slot
+        .max(slot.saturating_add(registry.vkey_add_delay));
+    require!(
+        vkey_entry.activation_slot < vkey_entry.deprecation_slot,
+        ErrorCode::InvalidZkVkeyWindow
+    );
+
+    registry.vkeys.push(vkey_entry);
+
+    Ok(())
+}
+
+/// Schedules (or, with the current slot, applies) the end of a vkey's
+/// acceptance window. Proofs for a deprecated guest are rejected from
+/// `deprecation_slot` on. A vkey is deprecated at most once and never in the
+/// past, so a revoked guest cannot be revived and a live one cannot be
+/// invalidated retroactively.
+pub fn deprecate_zk_vkey_impl(
+    ctx: Context<DeprecateZkVkey>,
+    vkey_hash: [u8; 32],
+    deprecation_slot: u64,
+) -> Result<()> {
+    require!(
+        deprecation_slot >= Clock::get()?.slot,
+        ErrorCode::ZkVkeyDeprecationInPast
+    );
+
+    let entry = ctx
+        .accounts
+        .zk_vkey_registry
+        .vkeys
+        .iter_mut()
+        .find(|entry| entry.vkey_hash == vkey_hash)
+        .ok_or(ErrorCode::ZkVkeyNotFound)?;
+
+    require!(
+        entry.deprecation_slot == u64::MAX,
+        ErrorCode::ZkVkeyAlreadyDeprecated
+    );
+
+    entry.deprecation_slot = deprecation_slot;
+
+    Ok(())
+}
+
+/// The delay can only grow: lowering it would let the authority skip the
+/// timelock on its next vkey addition.
+pub fn set_zk_vkey_add_delay_impl(
+    ctx: Context<SetZkVkeyAddDelay>,
+    vkey_add_delay: u64,
+) -> Result<()> {
+    let registry = &mut ctx.accounts.zk_vkey_registry;
+
+    require!(
+        vkey_add_delay >= registry.vkey_add_delay,
+        ErrorCode::ZkVkeyAddDelayDecrease
+    );
+    require!(
+        vkey_add_delay <= ZkVkeyRegistry::MAX_VKEY_ADD_DELAY,
+        ErrorCode::ZkVkeyAddDelayTooLarge
+    );
+
+    registry.vkey_add_delay = vkey_add_delay;
+
+    Ok(())
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("ZK verification key already registered")]
+    ZkVkeyAlreadyRegistered,
+    #[msg("ZK verification key not found")]
+    ZkVkeyNotFound,
+    #[msg("ZK verification key must activate before it is deprecated")]
+    InvalidZkVkeyWindow,
+    #[msg("ZK verification key is already deprecated")]
+    ZkVkeyAlreadyDeprecated,
+    #[msg("ZK verification key cannot be deprecated in the past")]
+    ZkVkeyDeprecationInPast,
+    #[msg("ZK verification key add delay exceeds the maximum allowed")]
+    ZkVkeyAddDelayTooLarge,
+    #[msg("ZK verification key add delay can only be increased")]
+    ZkVkeyAddDelayDecrease,
+    #[msg("Only the deployment admin may create the registry")]
+    NotAdmin,
+}
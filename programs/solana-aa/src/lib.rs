@@ -5,19 +5,27 @@ mod pda_seeds;
 pub mod types;
 mod utils;
 
+/// Proof verification and public-values decoding, exposed so host tooling
+/// (`zk/script verify`) checks proofs with exactly the program's code.
+pub use contract::auth::zk_oidc;
+
 use crate::contract::accounts::*;
 use crate::contract::auth::ek256::*;
 use crate::contract::auth::secp256r1_sha256::*;
 use crate::contract::auth::zk_oidc::Sp1Groth16Proof;
 use crate::contract::contract_lifecycle::*;
+use crate::contract::nonce_bitmap::*;
 use crate::contract::oidc_registry::*;
+use crate::contract::paymaster::*;
 use crate::contract::transaction::execute::*;
 use crate::contract::transaction_buffer::*;
+use crate::contract::zk_vkey_registry::*;
 use crate::types::{
     account::{AbstractAccount, AbstractAccountOperationAccounts, AccountId},
     identity::*,
-    oidc_key_registry::OidcKeyEntry,
+    oidc_key_registry::{OidcKeyEntry, OidcNonceBinding},
     transaction::transaction::{Transaction, WebAuthnAuthData},
+    zk_vkey_registry::ZkVkeyEntry,
 };
 
 declare_id!("2PYNfKSoM7rFJeMuvEidASxgpdPAXYascVDmH6jpBa7o");
@@ -41,37 +49,27 @@ pub mod solana_aa {
     pub fn init_storage(
         ctx: Context<InitStorage>,
         data_id: [u8; 32],
-        chunk_index: u16,
-        total_chunks: u16,
+        writers: Vec<Pubkey>,
+        data_len: u32,
         data_hash: [u8; 32],
-        chunk_data: Vec<u8>,
     ) -> Result<()> {
-        init_storage_impl(
-            ctx,
-            data_id,
-            chunk_index,
-            total_chunks,
-            data_hash,
-            chunk_data,
-        )
+        init_storage_impl(ctx, data_id, writers, data_len, data_hash)
+    }
+
+    pub fn extend_storage(ctx: Context<ExtendStorage>) -> Result<()> {
+        extend_storage_impl(ctx)
     }
 
     pub fn store_chunk(
         ctx: Context<StoreChunk>,
-        data_id: [u8; 32],
         chunk_index: u16,
-        total_chunks: u16,
-        data_hash: [u8; 32],
         chunk_data: Vec<u8>,
     ) -> Result<()> {
-        store_chunk_impl(
-            ctx,
-            data_id,
-            chunk_index,
-            total_chunks,
-            data_hash,
-            chunk_data,
-        )
+        store_chunk_impl(ctx, chunk_index, chunk_data)
+    }
+
+    pub fn finalize_storage(ctx: Context<FinalizeStorage>) -> Result<()> {
+        finalize_storage_impl(ctx)
     }
 
     pub fn retrieve_chunk(ctx: Context<RetrieveChunk>, chunk_index: u16) -> Result<Vec<u8>> {
@@ -86,6 +84,10 @@ pub mod solana_aa {
         close_storage_impl(ctx)
     }
 
+    pub fn reclaim_expired_storage(ctx: Context<ReclaimExpiredStorage>) -> Result<()> {
+        reclaim_expired_storage_impl(ctx)
+    }
+
     pub fn verify_eth(
         ctx: Context<VerifyEthereumSignature>,
         signed_message: Vec<u8>,
@@ -141,11 +143,21 @@ pub mod solana_aa {
             ctx.accounts.account_manager.admin,
             crate::types::account::ErrorCode::Unauthorized
         );
-        AbstractAccount::close_account(AbstractAccountOperationAccounts {
-            abstract_account: &mut ctx.accounts.abstract_account,
-            signer_info: ctx.accounts.signer.to_account_info(),
-            system_program_info: ctx.accounts.system_program.to_account_info(),
-        })
+        let signer_info = ctx.accounts.signer.to_account_info();
+        if let Some(nonce_bitmap) = ctx.accounts.nonce_bitmap.as_ref() {
+            let payer = ctx.accounts.nonce_bitmap_payer.as_ref().ok_or(
+                crate::contract::transaction::execute::ErrorCode::NonceBitmapPayerMismatch,
+            )?;
+            nonce_bitmap.close(payer.to_account_info())?;
+        }
+        AbstractAccount::close_account(
+            AbstractAccountOperationAccounts {
+                abstract_account: &mut ctx.accounts.abstract_account,
+                signer_info: signer_info.clone(),
+                system_program_info: ctx.accounts.system_program.to_account_info(),
+            },
+            &signer_info,
+        )
     }
 
     pub fn execute_ek256<'info>(
@@ -155,19 +167,136 @@ pub mod solana_aa {
         execute_ek256_impl(ctx, account_id)
     }
 
-    pub fn init_oidc_registry(ctx: Context<InitOidcRegistry>) -> Result<()> {
-        init_oidc_registry_impl(ctx)
+    pub fn execute_ek256_from_buffer<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteEk256FromBuffer<'info>>,
+        account_id: AccountId,
+    ) -> Result<()> {
+        execute_ek256_from_buffer_impl(ctx, account_id)
+    }
+
+    pub fn init_oidc_registry(
+        ctx: Context<InitOidcRegistry>,
+        iss_hash: [u8; 32],
+        iss: String,
+        clock_skew: u64,
+        nonce_binding: OidcNonceBinding,
+        key_add_delay: u64,
+    ) -> Result<()> {
+        init_oidc_registry_impl(ctx, iss_hash, iss, clock_skew, nonce_binding, key_add_delay)
+    }
+
+    pub fn propose_oidc_authorities(
+        ctx: Context<ProposeOidcAuthorities>,
+        authorities: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        propose_oidc_authorities_impl(ctx, authorities, threshold)
+    }
+
+    pub fn accept_oidc_authorities(ctx: Context<AcceptOidcAuthorities>) -> Result<()> {
+        accept_oidc_authorities_impl(ctx)
+    }
+
+    pub fn set_oidc_clock_skew(ctx: Context<SetOidcClockSkew>, clock_skew: u64) -> Result<()> {
+        set_oidc_clock_skew_impl(ctx, clock_skew)
+    }
+
+    pub fn set_oidc_key_add_delay(
+        ctx: Context<SetOidcKeyAddDelay>,
+        key_add_delay: u64,
+    ) -> Result<()> {
+        set_oidc_key_add_delay_impl(ctx, key_add_delay)
     }
 
     pub fn add_oidc_key(ctx: Context<AddOidcKey>, key_entry: OidcKeyEntry) -> Result<()> {
         add_oidc_key_impl(ctx, key_entry)
     }
 
-    pub fn remove_oidc_key(ctx: Context<RemoveOidcKey>, key_entry: OidcKeyEntry) -> Result<()> {
-        remove_oidc_key_impl(ctx, key_entry)
+    pub fn remove_oidc_key(ctx: Context<RemoveOidcKey>, pk_hash: [u8; 32]) -> Result<()> {
+        remove_oidc_key_impl(ctx, pk_hash)
+    }
+
+    pub fn close_oidc_registry(ctx: Context<CloseOidcRegistry>) -> Result<()> {
+        close_oidc_registry_impl(ctx)
+    }
+
+    pub fn init_zk_vkey_registry(
+        ctx: Context<InitZkVkeyRegistry>,
+        vkey_add_delay: u64,
+    ) -> Result<()> {
+        init_zk_vkey_registry_impl(ctx, vkey_add_delay)
+    }
+
+    pub fn add_zk_vkey(ctx: Context<AddZkVkey>, vkey_entry: ZkVkeyEntry) -> Result<()> {
+        add_zk_vkey_impl(ctx, vkey_entry)
     }
 
-    pub fn close_oidc_registry(_ctx: Context<CloseOidcRegistry>) -> Result<()> {
+    pub fn deprecate_zk_vkey(
+        ctx: Context<DeprecateZkVkey>,
+        vkey_hash: [u8; 32],
+        deprecation_slot: u64,
+    ) -> Result<()> {
+        deprecate_zk_vkey_impl(ctx, vkey_hash, deprecation_slot)
+    }
+
+    pub fn set_zk_vkey_add_delay(
+        ctx: Context<SetZkVkeyAddDelay>,
+        vkey_add_delay: u64,
+    ) -> Result<()> {
+        set_zk_vkey_add_delay_impl(ctx, vkey_add_delay)
+    }
+
+    pub fn init_paymaster(
+        ctx: Context<InitPaymaster>,
+        reimbursement: u64,
+        daily_budget: u64,
+        allowed_actions: u16,
+    ) -> Result<()> {
+        init_paymaster_impl(ctx, reimbursement, daily_budget, allowed_actions)
+    }
+
+    pub fn update_paymaster(
+        ctx: Context<UpdatePaymaster>,
+        reimbursement: u64,
+        daily_budget: u64,
+        allowed_actions: u16,
+    ) -> Result<()> {
+        update_paymaster_impl(ctx, reimbursement, daily_budget, allowed_actions)
+    }
+
+    pub fn add_sponsored_account(
+        ctx: Context<AddSponsoredAccount>,
+        account_id: AccountId,
+    ) -> Result<()> {
+        add_sponsored_account_impl(ctx, account_id)
+    }
+
+    pub fn remove_sponsored_account(
+        ctx: Context<RemoveSponsoredAccount>,
+        account_id: AccountId,
+    ) -> Result<()> {
+        remove_sponsored_account_impl(ctx, account_id)
+    }
+
+    pub fn withdraw_from_paymaster(
+        ctx: Context<WithdrawFromPaymaster>,
+        lamports: u64,
+    ) -> Result<()> {
+        withdraw_from_paymaster_impl(ctx, lamports)
+    }
+
+    pub fn close_paymaster(_ctx: Context<ClosePaymaster>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn init_nonce_bitmap(ctx: Context<InitNonceBitmap>, account_id: AccountId) -> Result<()> {
+        init_nonce_bitmap_impl(ctx, account_id)
+    }
+
+    pub fn close_nonce_bitmap(
+        _ctx: Context<CloseNonceBitmap>,
+        _account_id: AccountId,
+    ) -> Result<()> {
         Ok(())
     }
 
@@ -180,6 +309,22 @@ pub mod solana_aa {
         execute_zk_oidc_impl(ctx, account_id, transaction, groth16_proof)
     }
 
+    pub fn execute_zk_oidc_from_buffer<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteZkOidcFromBuffer<'info>>,
+        account_id: AccountId,
+    ) -> Result<()> {
+        execute_zk_oidc_from_buffer_impl(ctx, account_id)
+    }
+
+    pub fn execute_zk_oidc_aggregated<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteZkOidcAggregated<'info>>,
+        account_id: AccountId,
+        transaction: Transaction,
+        groth16_proof: Sp1Groth16Proof,
+    ) -> Result<()> {
+        execute_zk_oidc_aggregated_impl(ctx, account_id, transaction, groth16_proof)
+    }
+
     pub fn execute_webauthn<'info>(
         ctx: Context<'_, '_, '_, 'info, ExecuteWebauthn<'info>>,
         account_id: AccountId,
@@ -188,4 +333,11 @@ pub mod solana_aa {
     ) -> Result<()> {
         execute_webauthn_impl(ctx, account_id, transaction, auth)
     }
+
+    pub fn execute_webauthn_from_buffer<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteWebauthnFromBuffer<'info>>,
+        account_id: AccountId,
+    ) -> Result<()> {
+        execute_webauthn_from_buffer_impl(ctx, account_id)
+    }
 }
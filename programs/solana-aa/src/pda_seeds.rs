@@ -1,3 +1,7 @@
 pub const ABSTRACT_ACCOUNT_SEED: &[u8] = b"abstract_account";
 pub const ACCOUNT_MANAGER_SEED: &[u8] = b"account_manager";
 pub const OIDC_KEY_REGISTRY_SEED: &[u8] = b"oidc_key_registry";
+pub const ZK_VKEY_REGISTRY_SEED: &[u8] = b"zk_vkey_registry";
+pub const UNIFIED_STORAGE_SEED: &[u8] = b"unified_storage";
+pub const PAYMASTER_SEED: &[u8] = b"paymaster";
+pub const NONCE_BITMAP_SEED: &[u8] = b"nonce_bitmap";
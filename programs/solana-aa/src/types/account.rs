@@ -5,6 +5,13 @@ use anchor_lang::prelude::*;
 
 pub type AccountId = u64;
 pub type Nonce = u128;
+pub type NonceKey = u64;
+
+/// Splits a transaction nonce into its `nonce_key` (high 64 bits) and
+/// `sequence` (low 64 bits). Each key is an independent sequential lane.
+pub fn split_nonce(nonce: Nonce) -> (NonceKey, u64) {
+    ((nonce >> 64) as NonceKey, nonce as u64)
+}
 
 /**
 * Abstract Account PDA
@@ -19,13 +26,20 @@ pub type Nonce = u128;
 */
 #[account]
 pub struct AbstractAccount {
+    /// Sequence of nonce key 0, the lane every transaction used before keyed
+    /// nonces existed.
     pub nonce: Nonce,
 
+    /// Sequences of the other nonce keys, opened on first use.
+    pub nonce_keys: Vec<KeyedNonce>,
+
     // TODO: Benchmark other data structures; BtreeMap, HashMap, etc.
     // Considering ~10 identities per account, a Vec might be the best choice.
     // Vec avoid the overhead of Key-Value pair of BTreeMap and HashMap softening the usage of Heap and Stack.
     pub identities: Vec<IdentityWithPermissions>,
 
+    pub rent_payer: RentPayer,
+
     // PDA discriminator to optimize Anchor account validation
     pub bump: u8,
 }
@@ -34,18 +48,78 @@ impl AbstractAccount {
     const PDA_DISCRIMINATOR_SIZE: usize = 8;
     const NONCE_SIZE: usize = 16;
     const VEC_SIZE: usize = 4;
+    const RENT_PAYER_SIZE: usize = 1;
     const BUMP_SIZE: usize = 1;
 
     /// Caps on growth so a single account can't be inflated without bound
     /// (rent griefing / heap pressure). 16 identities is well above real use.
     const MAX_IDENTITIES: usize = 16;
+    const MAX_NONCE_KEYS: usize = 8;
     const MAX_ACCOUNT_SIZE: usize = 8 * 1024;
 
-    pub const INIT_SIZE: usize =
-        Self::PDA_DISCRIMINATOR_SIZE + Self::NONCE_SIZE + Self::VEC_SIZE + Self::BUMP_SIZE;
+    pub const INIT_SIZE: usize = Self::PDA_DISCRIMINATOR_SIZE
+        + Self::NONCE_SIZE
+        + Self::VEC_SIZE
+        + Self::VEC_SIZE
+        + Self::RENT_PAYER_SIZE
+        + Self::BUMP_SIZE;
+
+    /// The transaction nonce the account accepts next on `nonce_key`.
+    pub fn expected_nonce(&self, nonce_key: NonceKey) -> Nonce {
+        if nonce_key == 0 {
+            return self.nonce;
+        }
+
+        let sequence = self
+            .nonce_keys
+            .iter()
+            .find(|n| n.key == nonce_key)
+            .map_or(0, |n| n.sequence);
+        ((nonce_key as Nonce) << 64) | sequence as Nonce
+    }
+
+    pub fn has_nonce_key(&self, nonce_key: NonceKey) -> bool {
+        nonce_key == 0 || self.nonce_keys.iter().any(|n| n.key == nonce_key)
+    }
+
+    pub fn increment_nonce(&mut self, nonce_key: NonceKey) {
+        if nonce_key == 0 {
+            self.nonce = self.nonce.saturating_add(1);
+        } else if let Some(keyed_nonce) = self.nonce_keys.iter_mut().find(|n| n.key == nonce_key) {
+            keyed_nonce.sequence = keyed_nonce.sequence.saturating_add(1);
+        }
+    }
+
+    /// Starts tracking `nonce_key` at sequence 0, growing the account for it.
+    pub fn open_nonce_key<'info>(
+        abstract_account_operation_accounts: &mut AbstractAccountOperationAccounts<'_, 'info>,
+        nonce_key: NonceKey,
+    ) -> Result<()> {
+        let AbstractAccountOperationAccounts {
+            abstract_account,
+            signer_info,
+            system_program_info,
+        } = abstract_account_operation_accounts;
+
+        require!(
+            abstract_account.nonce_keys.len() < Self::MAX_NONCE_KEYS,
+            ErrorCode::TooManyNonceKeys
+        );
+
+        let new_size = abstract_account.to_account_info().data_len() + KeyedNonce::SIZE;
+        require!(
+            new_size <= Self::MAX_ACCOUNT_SIZE,
+            ErrorCode::AccountTooLarge
+        );
+
+        Self::resize(abstract_account, new_size, signer_info, system_program_info)?;
 
-    pub fn increment_nonce(&mut self) {
-        self.nonce = self.nonce.saturating_add(1);
+        abstract_account.nonce_keys.push(KeyedNonce {
+            key: nonce_key,
+            sequence: 0,
+        });
+
+        Ok(())
     }
 
     pub fn has_identity(&self, identity: &Identity) -> bool {
@@ -79,11 +153,16 @@ impl AbstractAccount {
             ErrorCode::TooManyIdentities
         );
 
-        let account_info = abstract_account.to_account_info();
-        let new_size = account_info.data_len() + identity_with_permissions.byte_size()?;
+        let new_size = abstract_account.to_account_info().data_len()
+            + identity_with_permissions.byte_size()?;
         require!(new_size <= Self::MAX_ACCOUNT_SIZE, ErrorCode::AccountTooLarge);
 
-        realloc_account(&account_info, new_size, &signer_info, &system_program_info)?;
+        Self::resize(
+            abstract_account,
+            new_size,
+            &signer_info,
+            &system_program_info,
+        )?;
 
         abstract_account.identities.push(identity_with_permissions);
 
@@ -111,27 +190,99 @@ impl AbstractAccount {
             .retain(|i| &i.identity != identity);
 
         if initial_len > abstract_account.identities.len() {
-            let account_info = abstract_account.to_account_info();
-            let new_size = account_info.data_len() - identity_size;
+            let new_size = abstract_account.to_account_info().data_len() - identity_size;
 
-            realloc_account(&account_info, new_size, &signer_info, &system_program_info)?;
+            Self::resize(
+                abstract_account,
+                new_size,
+                &signer_info,
+                &system_program_info,
+            )?;
         }
 
         Ok(())
     }
 
-    pub fn close_account(
-        abstract_account_operation_accounts: AbstractAccountOperationAccounts,
+    /// The whole balance, rent and any funds the account holds, goes to `recipient`
+    /// whatever the `rent_payer`: it is the account's to give away. Handing the
+    /// rent to the signer instead would pay whoever submits the removal first.
+    pub fn close_account<'info>(
+        abstract_account_operation_accounts: AbstractAccountOperationAccounts<'_, 'info>,
+        recipient: &AccountInfo<'info>,
     ) -> Result<()> {
-        let AbstractAccountOperationAccounts {
-            abstract_account,
-            signer_info,
-            ..
-        } = abstract_account_operation_accounts;
+        let account_info = abstract_account_operation_accounts
+            .abstract_account
+            .to_account_info();
 
-        close_pda(&abstract_account.to_account_info(), &signer_info)?;
+        close_pda(&account_info, recipient)?;
         Ok(())
     }
+
+    /// Pays `lamports` out of the account's own balance, which must stay
+    /// rent-exempt.
+    pub fn pay_from_account(
+        account_info: &AccountInfo,
+        recipient: &AccountInfo,
+        lamports: u64,
+    ) -> Result<()> {
+        let minimum_balance = Rent::get()?.minimum_balance(account_info.data_len());
+        let remaining = account_info
+            .lamports()
+            .checked_sub(lamports)
+            .filter(|remaining| *remaining >= minimum_balance)
+            .ok_or(ErrorCode::InsufficientAccountLamports)?;
+
+        **account_info.try_borrow_mut_lamports()? = remaining;
+        **recipient.try_borrow_mut_lamports()? += lamports;
+
+        Ok(())
+    }
+
+    /// Resizes the account's data, settling the rent difference with whoever the
+    /// `rent_payer` policy names.
+    fn resize<'info>(
+        abstract_account: &Account<'info, AbstractAccount>,
+        new_size: usize,
+        signer_info: &AccountInfo<'info>,
+        system_program_info: &AccountInfo<'info>,
+    ) -> Result<()> {
+        let account_info = abstract_account.to_account_info();
+
+        match abstract_account.rent_payer {
+            RentPayer::Relayer => {
+                realloc_account(&account_info, new_size, signer_info, system_program_info)
+            }
+            RentPayer::Account => {
+                require!(
+                    account_info.lamports() >= Rent::get()?.minimum_balance(new_size),
+                    ErrorCode::InsufficientAccountLamports
+                );
+                account_info.realloc(new_size, false)?;
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyedNonce {
+    pub key: NonceKey,
+    pub sequence: u64,
+}
+
+impl KeyedNonce {
+    pub const SIZE: usize = 8 + 8;
+}
+
+/// Who funds the account's rent as it grows, and receives the rent freed as it
+/// shrinks.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RentPayer {
+    /// The execute signer (usually a relayer) pays and is refunded.
+    Relayer,
+    /// The account pays from the lamports it holds beyond its rent-exempt
+    /// minimum, and keeps freed rent.
+    Account,
 }
 
 pub struct AbstractAccountOperationAccounts<'a, 'info> {
@@ -150,4 +301,8 @@ pub enum ErrorCode {
     AccountTooLarge,
     #[msg("Only the deployment admin may perform this operation")]
     Unauthorized,
+    #[msg("Account does not hold enough lamports to pay its own rent")]
+    InsufficientAccountLamports,
+    #[msg("Account has reached the maximum number of nonce keys")]
+    TooManyNonceKeys,
 }
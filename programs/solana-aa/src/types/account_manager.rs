@@ -18,7 +18,8 @@ pub struct AccountManager {
     pub chain_signatures_program_id: Pubkey,
 
     /// Deployment admin (set to the init signer). Only this key may call the
-    /// privileged account-close instruction used for administration/teardown.
+    /// privileged account-close instruction used for administration/teardown,
+    /// and create the ZK vkey and OIDC key registries.
     pub admin: Pubkey,
 
     // PDA discriminator to optimize Anchor account validation
@@ -1,5 +1,8 @@
 pub mod account;
 pub mod account_manager;
 pub mod identity;
+pub mod nonce_bitmap;
 pub mod oidc_key_registry;
+pub mod paymaster;
 pub mod transaction;
+pub mod zk_vkey_registry;
@@ -0,0 +1,87 @@
+use anchor_lang::prelude::*;
+
+use super::account::{AccountId, Nonce};
+
+/// Permit2-style unordered nonces of one abstract account.
+///
+/// A transaction in `ReplayProtection::Unordered` mode may use any unused nonce
+/// in `[base, base + WINDOW)`, in any order. A nonce past the window slides it
+/// forward so that nonce is the last slot, retiring everything below the new
+/// `base` as if it were used. One nonce may slide it by at most `MAX_STRIDE`,
+/// so a single transaction cannot retire the whole window of pending ones.
+/// Transactions carry an expiry slot, so clients that draw nonces roughly in
+/// order only lose nonces of transactions that would have expired anyway.
+#[account]
+pub struct NonceBitmap {
+    pub account_id: AccountId,
+    pub base: Nonce,
+    /// Bit `i` is set once `base + i` has been used.
+    pub bits: [u64; 16],
+    /// Funded the bitmap, and is refunded by `close_nonce_bitmap`.
+    pub payer: Pubkey,
+
+    // PDA discriminator to optimize Anchor account validation
+    pub bump: u8,
+}
+
+impl NonceBitmap {
+    const PDA_DISCRIMINATOR_SIZE: usize = 8;
+    const ACCOUNT_ID_SIZE: usize = 8;
+    const BASE_SIZE: usize = 16;
+    const WORDS: usize = 16;
+    const PAYER_SIZE: usize = 32;
+    const BUMP_SIZE: usize = 1;
+
+    pub const WINDOW: Nonce = (Self::WORDS * 64) as Nonce;
+    pub const MAX_STRIDE: Nonce = Self::WINDOW / 4;
+
+    pub const SIZE: usize = Self::PDA_DISCRIMINATOR_SIZE
+        + Self::ACCOUNT_ID_SIZE
+        + Self::BASE_SIZE
+        + Self::WORDS * 8
+        + Self::PAYER_SIZE
+        + Self::BUMP_SIZE;
+
+    /// Marks `nonce` used, sliding the window forward first if it lies past it.
+    pub fn consume(&mut self, nonce: Nonce) -> Result<()> {
+        require!(nonce >= self.base, ErrorCode::NonceAlreadyUsed);
+        require!(
+            nonce - self.base < Self::WINDOW + Self::MAX_STRIDE,
+            ErrorCode::NonceTooFarAhead
+        );
+
+        if nonce - self.base >= Self::WINDOW {
+            self.advance(nonce - (Self::WINDOW - 1));
+        }
+
+        let index = (nonce - self.base) as usize;
+        let (word, mask) = (index / 64, 1u64 << (index % 64));
+        require!(self.bits[word] & mask == 0, ErrorCode::NonceAlreadyUsed);
+        self.bits[word] |= mask;
+
+        Ok(())
+    }
+
+    fn advance(&mut self, new_base: Nonce) {
+        let shift = (new_base - self.base) as usize;
+        let mut bits = [0u64; Self::WORDS];
+
+        for index in 0..(Self::WINDOW as usize - shift) {
+            let from = index + shift;
+            if self.bits[from / 64] & (1u64 << (from % 64)) != 0 {
+                bits[index / 64] |= 1u64 << (index % 64);
+            }
+        }
+
+        self.bits = bits;
+        self.base = new_base;
+    }
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Unordered nonce was already used or has been retired")]
+    NonceAlreadyUsed,
+    #[msg("Unordered nonce would slide the window further than MAX_STRIDE")]
+    NonceTooFarAhead,
+}
@@ -1,14 +1,40 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash as sha256;
 
-/// Registry of OIDC provider signing keys trusted by the program.
+/// Registry of one OIDC provider's signing keys trusted by the program.
 ///
-/// `execute_zk_oidc` only accepts proofs whose `(iss, pk_hash)` pair is present
-/// here, pinning the JWKS keys a JWT may be signed with. Without this check a
-/// proof generated against any self-chosen RSA key would verify. The authority
-/// is expected to follow provider key rotation (e.g. Google's JWKS endpoint).
+/// One PDA per issuer, seeded by `sha256(iss)`, so each provider's keys live
+/// in a small account that grows and shrinks with its own JWKS.
+/// `execute_zk_oidc` only accepts proofs whose `pk_hash` is present (and inside
+/// its validity window) in the registry of the proof's `iss`, pinning the JWKS
+/// keys a JWT may be signed with. Without this check a proof generated against
+/// any self-chosen key would verify. The authorities are expected to follow
+/// provider key rotation (e.g. Google's JWKS endpoint), scheduling new keys
+/// through `not_before` ahead of the provider switching to them.
+///
+/// Governed by an M-of-N authority set: every change needs `threshold`
+/// distinct authority signatures. New keys only become valid `key_add_delay`
+/// seconds after they are added, giving users time to react to a malicious
+/// addition, while removals take effect immediately.
 #[account]
 pub struct OidcKeyRegistry {
-    pub authority: Pubkey,
+    pub authorities: Vec<Pubkey>,
+    pub threshold: u8,
+    /// Authority set proposed by the current one; takes over once `pending_threshold`
+    /// of its members accept. Empty when no transfer is in flight.
+    pub pending_authorities: Vec<Pubkey>,
+    pub pending_threshold: u8,
+    /// Minimum seconds between adding a key and its `not_before`.
+    pub key_add_delay: u64,
+    /// `sha256(iss)`, the PDA seed. Stored so later instructions can re-derive
+    /// the address without rehashing the issuer.
+    pub iss_hash: [u8; 32],
+    pub iss: String,
+    /// Tolerance (seconds) applied to the JWT `iat`/`exp` check against the
+    /// cluster `Clock`, absorbing drift between provider and validator clocks.
+    pub clock_skew: u64,
+    /// How this issuer's tokens carry the transaction binding.
+    pub nonce_binding: OidcNonceBinding,
     pub keys: Vec<OidcKeyEntry>,
 
     // PDA discriminator to optimize Anchor account validation
@@ -17,30 +43,184 @@ pub struct OidcKeyRegistry {
 
 #[derive(Debug, AnchorSerialize, AnchorDeserialize, Eq, PartialEq, Clone)]
 pub struct OidcKeyEntry {
-    pub iss: String,
-    /// SHA-256 hash of the provider RSA public key (SPKI DER), as committed
-    /// by the guest program.
+    /// SHA-256 hash of the provider public key (SPKI DER), as committed by the
+    /// guest program.
     pub pk_hash: [u8; 32],
+    pub key_type: OidcKeyType,
+    /// JWKS `kid` of the key. Informational: lets operators match entries to
+    /// the provider's published JWKS when rotating.
+    pub kid: String,
+    /// Unix timestamp from which the key is accepted.
+    pub not_before: u64,
+    /// Unix timestamp from which the key is no longer accepted. `u64::MAX`
+    /// for no scheduled expiry.
+    pub not_after: u64,
+}
+
+/// Kind of provider signing key, determining the JWT `alg` a token signed by
+/// it must carry. Consumer providers (Google, Apple, Microsoft) use RSA;
+/// self-hosted IdPs (Keycloak, Okta) commonly use P-256 or Ed25519.
+///
+/// New variants must be appended to keep Borsh enum tags stable.
+#[derive(Debug, AnchorSerialize, AnchorDeserialize, Eq, PartialEq, Clone, Copy)]
+pub enum OidcKeyType {
+    Rsa,
+    P256,
+    Ed25519,
+}
+
+impl OidcKeyType {
+    /// The JWT header `alg` (as committed by the guest) this key type signs.
+    pub fn jwt_alg(&self) -> &'static str {
+        match self {
+            OidcKeyType::Rsa => "RS256",
+            OidcKeyType::P256 => "ES256",
+            OidcKeyType::Ed25519 => "EdDSA",
+        }
+    }
+}
+
+/// Where and in what form a provider echoes the transaction nonce
+/// (`hex(sha256(borsh(transaction)))`) back in its ID token.
+///
+/// New variants must be appended to keep Borsh enum tags stable.
+#[derive(Debug, AnchorSerialize, AnchorDeserialize, Eq, PartialEq, Clone)]
+pub enum OidcNonceBinding {
+    /// Standard `nonce` claim holding the transaction nonce verbatim.
+    Raw,
+    /// Standard `nonce` claim holding `hex(sha256(transaction nonce))`, for
+    /// providers that hash the request nonce before embedding it (e.g. Apple).
+    Sha256,
+    /// The transaction nonce verbatim, in the named custom claim.
+    CustomClaim(String),
+}
+
+impl OidcNonceBinding {
+    const STANDARD_CLAIM: &'static str = "nonce";
+
+    /// Claim the guest must have read the nonce from.
+    pub fn claim_name(&self) -> &str {
+        match self {
+            OidcNonceBinding::Raw | OidcNonceBinding::Sha256 => Self::STANDARD_CLAIM,
+            OidcNonceBinding::CustomClaim(claim) => claim,
+        }
+    }
+
+    /// Claim value a token bound to `transaction_nonce` must carry.
+    pub fn expected_claim_value(&self, transaction_nonce: &str) -> String {
+        match self {
+            OidcNonceBinding::Raw | OidcNonceBinding::CustomClaim(_) => {
+                transaction_nonce.to_string()
+            }
+            OidcNonceBinding::Sha256 => {
+                hex::encode(sha256(transaction_nonce.as_bytes()).to_bytes())
+            }
+        }
+    }
+
+    pub fn byte_size(&self) -> usize {
+        match self {
+            OidcNonceBinding::Raw | OidcNonceBinding::Sha256 => 1,
+            OidcNonceBinding::CustomClaim(claim) => 1 + 4 + claim.len(),
+        }
+    }
 }
 
 impl OidcKeyEntry {
     pub fn byte_size(&self) -> usize {
-        4 + self.iss.len() + 32
+        32 + 1 + 4 + self.kid.len() + 8 + 8
+    }
+
+    pub fn is_valid_at(&self, now: u64) -> bool {
+        self.not_before <= now && now < self.not_after
     }
 }
 
 impl OidcKeyRegistry {
     const PDA_DISCRIMINATOR_SIZE: usize = 8;
-    const AUTHORITY_SIZE: usize = 32;
+    pub const AUTHORITY_SIZE: usize = 32;
+    const THRESHOLD_SIZE: usize = 1;
+    const KEY_ADD_DELAY_SIZE: usize = 8;
+    const ISS_HASH_SIZE: usize = 32;
+    const STRING_PREFIX_SIZE: usize = 4;
+    const CLOCK_SKEW_SIZE: usize = 8;
     const VEC_SIZE: usize = 4;
     const BUMP_SIZE: usize = 1;
 
-    pub const INIT_SIZE: usize =
-        Self::PDA_DISCRIMINATOR_SIZE + Self::AUTHORITY_SIZE + Self::VEC_SIZE + Self::BUMP_SIZE;
+    /// Upper bound on `clock_skew` so the authorities cannot effectively disable
+    /// token expiry. Five minutes matches common OIDC library defaults.
+    pub const MAX_CLOCK_SKEW: u64 = 5 * 60;
+
+    /// Upper bound on `key_add_delay`, so a misconfigured registry cannot
+    /// indefinitely block provider key rotation.
+    pub const MAX_KEY_ADD_DELAY: u64 = 30 * 24 * 60 * 60;
+
+    pub const MAX_AUTHORITIES: usize = 10;
+
+    /// Longest accepted `kid`. JWKS kids are short identifiers (Google: 40 hex
+    /// chars).
+    pub const MAX_KID_LEN: usize = 64;
+
+    /// Size of an empty registry, excluding the variable-length `iss` bytes,
+    /// `nonce_binding` and authority set.
+    pub const INIT_SIZE: usize = Self::PDA_DISCRIMINATOR_SIZE
+        + Self::VEC_SIZE
+        + Self::THRESHOLD_SIZE
+        + Self::VEC_SIZE
+        + Self::THRESHOLD_SIZE
+        + Self::KEY_ADD_DELAY_SIZE
+        + Self::ISS_HASH_SIZE
+        + Self::STRING_PREFIX_SIZE
+        + Self::CLOCK_SKEW_SIZE
+        + Self::VEC_SIZE
+        + Self::BUMP_SIZE;
+
+    pub fn iss_hash(iss: &str) -> [u8; 32] {
+        sha256(iss.as_bytes()).to_bytes()
+    }
+
+    pub fn find(&self, pk_hash: &[u8; 32]) -> Option<&OidcKeyEntry> {
+        self.keys.iter().find(|key| &key.pk_hash == pk_hash)
+    }
+
+    pub fn contains(&self, pk_hash: &[u8; 32]) -> bool {
+        self.find(pk_hash).is_some()
+    }
+
+    /// A well-formed authority set: 1..=MAX_AUTHORITIES distinct keys and a
+    /// threshold between 1 and their count.
+    pub fn is_valid_authority_set(authorities: &[Pubkey], threshold: u8) -> bool {
+        let distinct = authorities
+            .iter()
+            .enumerate()
+            .all(|(i, key)| !authorities[..i].contains(key));
+
+        !authorities.is_empty()
+            && authorities.len() <= Self::MAX_AUTHORITIES
+            && distinct
+            && threshold >= 1
+            && usize::from(threshold) <= authorities.len()
+    }
+
+    /// Whether `signers` include at least `threshold` of the current authorities.
+    pub fn is_approved_by(&self, signers: &[Pubkey]) -> bool {
+        Self::count_approvals(&self.authorities, signers) >= usize::from(self.threshold)
+    }
+
+    /// Whether `signers` include at least `pending_threshold` of the proposed
+    /// authorities.
+    pub fn is_accepted_by(&self, signers: &[Pubkey]) -> bool {
+        !self.pending_authorities.is_empty()
+            && Self::count_approvals(&self.pending_authorities, signers)
+                >= usize::from(self.pending_threshold)
+    }
 
-    pub fn contains(&self, iss: &str, pk_hash: &[u8; 32]) -> bool {
-        self.keys
+    // Authority sets hold distinct keys, so counting members present among the
+    // signers cannot count one signer twice.
+    fn count_approvals(authorities: &[Pubkey], signers: &[Pubkey]) -> usize {
+        authorities
             .iter()
-            .any(|key| key.iss == iss && &key.pk_hash == pk_hash)
+            .filter(|authority| signers.contains(authority))
+            .count()
     }
 }
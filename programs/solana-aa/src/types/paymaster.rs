@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+
+use super::account::AccountId;
+
+/// Lamports a sponsor set aside to reimburse relayers of `execute_*`
+/// transactions, and the rules they are paid under.
+///
+/// A relayer that passes the paymaster to an `execute_*` instruction is paid
+/// `reimbursement` from the PDA once the transaction is authorized, provided the
+/// account is sponsored, the action kind is allowed and the account's spend for
+/// the day stays within `daily_budget`. Anyone may fund the PDA with a plain
+/// transfer; only the sponsor may change the rules or withdraw.
+#[account]
+pub struct Paymaster {
+    pub sponsor: Pubkey,
+    /// Lamports paid to the relayer per sponsored execution.
+    pub reimbursement: u64,
+    /// Most lamports reimbursed for one account per day (UTC).
+    pub daily_budget: u64,
+    /// Bit `i` allows the `Action` with Borsh variant index `i`; see
+    /// `Action::mask`.
+    pub allowed_actions: u16,
+    pub accounts: Vec<SponsoredAccount>,
+
+    // PDA discriminator to optimize Anchor account validation
+    pub bump: u8,
+}
+
+#[derive(Debug, AnchorSerialize, AnchorDeserialize, Eq, PartialEq, Clone)]
+pub struct SponsoredAccount {
+    pub account_id: AccountId,
+    /// Day (`unix_timestamp / 86400`) that `spent` counts for.
+    pub day: u64,
+    pub spent: u64,
+}
+
+impl SponsoredAccount {
+    pub const SIZE: usize = 8 + 8 + 8;
+}
+
+impl Paymaster {
+    const PDA_DISCRIMINATOR_SIZE: usize = 8;
+    const SPONSOR_SIZE: usize = 32;
+    const REIMBURSEMENT_SIZE: usize = 8;
+    const DAILY_BUDGET_SIZE: usize = 8;
+    const ALLOWED_ACTIONS_SIZE: usize = 2;
+    const VEC_SIZE: usize = 4;
+    const BUMP_SIZE: usize = 1;
+
+    pub const MAX_ACCOUNTS: usize = 64;
+
+    pub const INIT_SIZE: usize = Self::PDA_DISCRIMINATOR_SIZE
+        + Self::SPONSOR_SIZE
+        + Self::REIMBURSEMENT_SIZE
+        + Self::DAILY_BUDGET_SIZE
+        + Self::ALLOWED_ACTIONS_SIZE
+        + Self::VEC_SIZE
+        + Self::BUMP_SIZE;
+
+    pub fn find(&self, account_id: AccountId) -> Option<&SponsoredAccount> {
+        self.accounts
+            .iter()
+            .find(|entry| entry.account_id == account_id)
+    }
+}
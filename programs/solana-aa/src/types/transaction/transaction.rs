@@ -1,8 +1,9 @@
 use anchor_lang::prelude::*;
 
 use crate::types::{
-    account::AccountId,
+    account::{AccountId, RentPayer},
     identity::{Identity, IdentityWithPermissions},
+    oidc_key_registry::OidcKeyEntry,
 };
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -48,13 +49,43 @@ It's mandatory to provide it's not possible to compute from the signed data.
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct Transaction {
     pub account_id: AccountId,
+    /// Under `ReplayProtection::Sequential`, `nonce_key << 64 | sequence`:
+    /// transactions on different keys don't wait on each other. Key 0 is the
+    /// account's original nonce. Under `Unordered`, a nonce of the account's
+    /// `NonceBitmap`.
     pub nonce: u128,
     pub action: Action,
+    pub fee: Option<TransactionFee>,
+    /// When set, only this key may submit the transaction as the execute
+    /// signer, so a party who sees it in transit cannot front-run it.
+    pub relayer: Option<Pubkey>,
+    pub replay_protection: ReplayProtection,
+}
+
+/// How `Transaction.nonce` guards against replay.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReplayProtection {
+    /// The nonce must be the next one on its nonce key.
+    Sequential,
+    /// The nonce may be any unused one in the account's `NonceBitmap`, passed to
+    /// the execute instruction, and the transaction is void after `expiry_slot`.
+    Unordered { expiry_slot: u64 },
+}
+
+/// Tip the account pays from its own lamports once the action succeeds, so a
+/// relayer with no other stake in the transaction will land it. `recipient`
+/// must be the execute signer or the writable `fee_recipient` account. A
+/// `RemoveAccount` leaves nothing to pay from, so one carrying a fee is
+/// rejected; name the relayer its `refund_to` instead.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct TransactionFee {
+    pub recipient: Pubkey,
+    pub lamports: u64,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub enum Action {
-    RemoveAccount,
+    RemoveAccount(RemoveAccountRequest),
     /*
     Credentials must contain the signature of account_id, nonce, action, permissions to:
     1. Prove ownership of the IdentityWithPermissions
@@ -66,6 +97,47 @@ pub enum Action {
     AddIdentity(IdentityWithPermissions),
     RemoveIdentity(Identity),
     Sign(SignRequest),
+    /*
+    OIDC registry governance with the account as one of the registry
+    authorities. The registry is passed as the first remaining account, followed
+    by any co-signing authorities.
+    */
+    AddOidcKey(AddOidcKeyRequest),
+    RemoveOidcKey(RemoveOidcKeyRequest),
+    AcceptOidcAuthorities(AcceptOidcAuthoritiesRequest),
+    SetRentPayer(RentPayer),
+    // More registry governance, laid out like the OIDC actions above.
+    ProposeOidcAuthorities(ProposeOidcAuthoritiesRequest),
+    SetOidcClockSkew(SetOidcClockSkewRequest),
+    SetOidcKeyAddDelay(SetOidcKeyAddDelayRequest),
+}
+
+impl Action {
+    /// Bit of the action's kind, its Borsh variant index, in a
+    /// `Paymaster::allowed_actions` mask.
+    pub fn mask(&self) -> u16 {
+        let index = match self {
+            Action::RemoveAccount(_) => 0,
+            Action::AddIdentity(_) => 1,
+            Action::RemoveIdentity(_) => 2,
+            Action::Sign(_) => 3,
+            Action::AddOidcKey(_) => 4,
+            Action::RemoveOidcKey(_) => 5,
+            Action::AcceptOidcAuthorities(_) => 6,
+            Action::SetRentPayer(_) => 7,
+            Action::ProposeOidcAuthorities(_) => 8,
+            Action::SetOidcClockSkew(_) => 9,
+            Action::SetOidcKeyAddDelay(_) => 10,
+        };
+        1 << index
+    }
+}
+
+/// `refund_to` receives the account's lamports. Binding it in the signed
+/// transaction keeps whoever relays the close from claiming the rent.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct RemoveAccountRequest {
+    pub refund_to: Pubkey,
 }
 
 /// Request forwarded to the Sig Network chain-signatures `sign` instruction.
@@ -82,6 +154,48 @@ pub struct SignRequest {
     pub params: String,
 }
 
+/// `iss_hash` names the issuer registry (`sha256(iss)`, its PDA seed), so the
+/// signed transaction cannot be replayed against another issuer's registry.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct AddOidcKeyRequest {
+    pub iss_hash: [u8; 32],
+    pub key_entry: OidcKeyEntry,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct RemoveOidcKeyRequest {
+    pub iss_hash: [u8; 32],
+    pub pk_hash: [u8; 32],
+}
+
+/// Lets an account proposed as a registry authority accept the transfer,
+/// which it cannot sign for directly.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct AcceptOidcAuthoritiesRequest {
+    pub iss_hash: [u8; 32],
+}
+
+/// Lets an account governing a registry hand it on, as
+/// `propose_oidc_authorities` does for signing authorities.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ProposeOidcAuthoritiesRequest {
+    pub iss_hash: [u8; 32],
+    pub authorities: Vec<Pubkey>,
+    pub threshold: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct SetOidcClockSkewRequest {
+    pub iss_hash: [u8; 32],
+    pub clock_skew: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct SetOidcKeyAddDelayRequest {
+    pub iss_hash: [u8; 32],
+    pub key_add_delay: u64,
+}
+
 /// Auxiliary data for the WebAuthn execute path: the raw clientDataJSON (whose
 /// `challenge` binds the transaction) and the authenticatorData (whose first 32
 /// bytes are the rpIdHash). Both are re-bound to the secp256r1-verified message.
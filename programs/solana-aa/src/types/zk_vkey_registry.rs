@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+
+/// Registry of SP1 guest verification keys accepted by `execute_zk_oidc`.
+///
+/// Each entry is live from `activation_slot` until `deprecation_slot`, so a
+/// guest upgrade registers the new vkey ahead of time and deprecates the old
+/// one once in-flight proofs have drained — no program redeploy, and no moment
+/// where every outstanding proof is invalidated at once.
+///
+/// A new vkey only activates `vkey_add_delay` slots after it is added, so a
+/// compromised authority cannot get a malicious guest accepted before the
+/// addition is noticed. Deprecation is one-way and never retroactive. Only the
+/// deployment admin may create the registry, and it is never closed, so the
+/// delay cannot be reset by re-creating it.
+#[account]
+pub struct ZkVkeyRegistry {
+    pub authority: Pubkey,
+    pub vkey_add_delay: u64,
+    pub vkeys: Vec<ZkVkeyEntry>,
+
+    // PDA discriminator to optimize Anchor account validation
+    pub bump: u8,
+}
+
+#[derive(Debug, AnchorSerialize, AnchorDeserialize, Eq, PartialEq, Clone)]
+pub struct ZkVkeyEntry {
+    /// SP1 verification key hash of the guest (`vk.bytes32()`), raw bytes.
+    pub vkey_hash: [u8; 32],
+    pub activation_slot: u64,
+    /// First slot at which the vkey is no longer accepted. `u64::MAX` means
+    /// not deprecated; kept as a plain `u64` (not `Option`) so deprecating an
+    /// entry never changes its serialized size.
+    pub deprecation_slot: u64,
+}
+
+impl ZkVkeyEntry {
+    pub const SIZE: usize = 32 + 8 + 8;
+
+    pub fn is_active_at(&self, slot: u64) -> bool {
+        self.activation_slot <= slot && slot < self.deprecation_slot
+    }
+}
+
+impl ZkVkeyRegistry {
+    const PDA_DISCRIMINATOR_SIZE: usize = 8;
+    const AUTHORITY_SIZE: usize = 32;
+    const VKEY_ADD_DELAY_SIZE: usize = 8;
+    const VEC_SIZE: usize = 4;
+    const BUMP_SIZE: usize = 1;
+
+    /// Upper bound on `vkey_add_delay` (~30 days of 400ms slots), so a
+    /// misconfigured registry cannot indefinitely block guest upgrades.
+    pub const MAX_VKEY_ADD_DELAY: u64 = 30 * 24 * 60 * 60 * 5 / 2;
+
+    pub const INIT_SIZE: usize = Self::PDA_DISCRIMINATOR_SIZE
+        + Self::AUTHORITY_SIZE
+        + Self::VKEY_ADD_DELAY_SIZE
+        + Self::VEC_SIZE
+        + Self::BUMP_SIZE;
+
+    pub fn find(&self, vkey_hash: &[u8; 32]) -> Option<&ZkVkeyEntry> {
+        self.vkeys.iter().find(|entry| &entry.vkey_hash == vkey_hash)
+    }
+
+    pub fn is_active(&self, vkey_hash: &[u8; 32], slot: u64) -> bool {
+        self.find(vkey_hash).is_some_and(|entry| entry.is_active_at(slot))
+    }
+}
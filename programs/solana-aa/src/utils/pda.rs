@@ -1,5 +1,5 @@
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::{program::invoke, system_instruction};
+use anchor_lang::solana_program::{program::invoke, system_instruction, system_program};
 
 /// Reallocates an account's data to a new size.
 /// This function handles the reallocation of account data by calculating the rent exemption
@@ -52,8 +52,10 @@ pub fn realloc_account<'info>(
 }
 
 /// Closes a PDA account by transferring its lamports to a recipient.
-/// This function transfers all lamports from the PDA to the recipient and clears its data,
-/// effectively making it unusable.
+/// This function transfers all lamports from the PDA to the recipient, then tombstones it:
+/// the account is handed to the system program with no data, which Anchor treats as closed.
+/// Anchor therefore skips writing the in-memory account back on exit, and lamports sent to
+/// the address later in the same transaction cannot revive it as a program account.
 ///
 /// # Arguments
 ///
@@ -71,10 +73,9 @@ pub fn close_pda<'info>(
     **account.try_borrow_mut_lamports()? = 0;
     **recipient.try_borrow_mut_lamports()? += account_lamports;
 
-    let mut data = account.try_borrow_mut_data()?;
-    for byte in data.iter_mut() {
-        *byte = 0;
-    }
+    account.try_borrow_mut_data()?.fill(0);
+    account.assign(&system_program::ID);
+    account.realloc(0, false)?;
 
     Ok(())
 }
@@ -23,6 +23,7 @@ import { normalizeObject } from "../utils/utils";
 const PRIVATE_KEY =
   "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80" as const;
 const ETH_ADDRESS = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+const REFUND_TO = anchor.web3.Keypair.generate().publicKey.toBytes();
 
 describe("Ethereum Signature Verification", () => {
   const provider = anchor.getProvider() as anchor.AnchorProvider;
@@ -98,6 +99,9 @@ describe("Ethereum Signature Verification", () => {
           },
         },
       },
+      fee: null,
+      relayer: null,
+      replay_protection: { Sequential: {} },
     };
 
     const { deserializedTransaction } = await verifyEthereumSignature(
@@ -114,7 +118,10 @@ describe("Ethereum Signature Verification", () => {
     const transaction: Transaction = {
       account_id: BigInt(1),
       nonce: BigInt(Date.now()),
-      action: { RemoveAccount: {} },
+      action: { RemoveAccount: { refund_to: REFUND_TO } },
+      fee: null,
+      relayer: null,
+      replay_protection: { Sequential: {} },
     };
 
     const { deserializedTransaction } = await verifyEthereumSignature(
@@ -138,6 +145,9 @@ describe("Ethereum Signature Verification", () => {
       account_id: BigInt(1),
       nonce: BigInt(Date.now()),
       action: { RemoveIdentity: identity },
+      fee: null,
+      relayer: null,
+      replay_protection: { Sequential: {} },
     };
 
     const { deserializedTransaction } = await verifyEthereumSignature(
@@ -168,6 +178,9 @@ describe("Ethereum Signature Verification", () => {
       account_id: BigInt(1),
       nonce: BigInt(Date.now()),
       action: { AddIdentity: identityWithPermissions },
+      fee: null,
+      relayer: null,
+      replay_protection: { Sequential: {} },
     };
 
     const { deserializedTransaction } = await verifyEthereumSignature(
@@ -190,7 +203,10 @@ describe("Ethereum Signature Verification", () => {
   const sampleTransaction: Transaction = {
     account_id: 1n,
     nonce: 7n,
-    action: { RemoveAccount: {} },
+    action: { RemoveAccount: { refund_to: REFUND_TO } },
+    fee: null,
+    relayer: null,
+    replay_protection: { Sequential: {} },
   };
   const sampleMessage = Buffer.from(
     borshUtils.serialize.transaction(sampleTransaction)
@@ -335,7 +351,10 @@ describe("Ethereum Signature Verification", () => {
       borshUtils.serialize.transaction({
         account_id: 2n,
         nonce: 9n,
-        action: { RemoveAccount: {} },
+        action: { RemoveAccount: { refund_to: REFUND_TO } },
+        fee: null,
+        relayer: null,
+        replay_protection: { Sequential: {} },
       })
     );
     try {
@@ -13,12 +13,15 @@ import {
   cleanUpProgramState,
   findAbstractAccountPDA,
   findAccountManagerPDA,
+  findNonceBitmapPDA,
 } from "../utils/program";
 import { buildEthereumIdentity } from "../utils/identity";
 import { privateKeyToAccount } from "viem/accounts";
 import { BN } from "bn.js";
 import { SolanaAa } from "../target/types/solana_aa";
 import { assert } from "chai";
+import { writeTransactionBuffer } from "../utils/transaction-buffer";
+import { executeEk256Action, keyedNonce } from "../utils/test-helpers";
 
 const PRIVATE_KEY =
   "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80" as const;
@@ -83,6 +86,63 @@ describe("Execute Ek256", () => {
     return txSignature;
   }
 
+  // Uploads `transaction` to a buffer of the provider wallet and executes it,
+  // with the secp256k1 instruction signing its keccak256 digest.
+  // `signedTransaction` overrides what the digest is taken over, `relayer`
+  // submits the execution in place of the wallet, and `owner` overrides the
+  // account the buffer's rent is refunded to.
+  async function executeEk256FromBuffer({
+    transaction,
+    privateKey,
+    accountId,
+    signedTransaction = transaction,
+    relayer,
+    owner = provider.wallet.publicKey,
+  }: {
+    transaction: Transaction;
+    privateKey: Hex;
+    accountId: bigint;
+    signedTransaction?: Transaction;
+    relayer?: anchor.web3.Keypair;
+    owner?: anchor.web3.PublicKey;
+  }) {
+    const unifiedStorage = await writeTransactionBuffer(
+      program,
+      borshUtils.serialize.transaction(transaction)
+    );
+    const digest = toBytes(
+      keccak256(borshUtils.serialize.transaction(signedTransaction))
+    );
+
+    const ethSignature = await signWithEthereum({
+      hash: keccak256(digest),
+      privateKey,
+    });
+    const { signature, recoveryId } = parseEthereumSignature(
+      ethSignature.signature
+    );
+    const verificationInstruction = createSecp256k1VerificationInstruction(
+      signature,
+      recoveryId,
+      ethereumAddressToBytes(ethSignature.address),
+      Buffer.from(digest)
+    );
+
+    const txSignature = await program.methods
+      .executeEk256FromBuffer(new BN(accountId.toString()))
+      .accountsPartial({
+        signer: relayer?.publicKey ?? provider.wallet.publicKey,
+        unifiedStorage,
+        owner,
+      })
+      .preInstructions([verificationInstruction])
+      .signers(relayer ? [relayer] : [])
+      .rpc();
+    await confirmTransaction(provider.connection, txSignature);
+
+    return { unifiedStorage, txSignature };
+  }
+
   it("should create account and execute AddIdentity transaction", async () => {
     const accountId = new BN(0);
     const account = privateKeyToAccount(PRIVATE_KEY);
@@ -110,6 +170,9 @@ describe("Execute Ek256", () => {
           },
         },
       },
+      fee: null,
+      relayer: null,
+      replay_protection: { Sequential: {} },
     };
 
     await executeEk256({
@@ -167,6 +230,9 @@ describe("Execute Ek256", () => {
           },
         },
       },
+      fee: null,
+      relayer: null,
+      replay_protection: { Sequential: {} },
     };
 
     await executeEk256({
@@ -186,6 +252,9 @@ describe("Execute Ek256", () => {
           },
         },
       },
+      fee: null,
+      relayer: null,
+      replay_protection: { Sequential: {} },
     };
 
     await executeEk256({
@@ -237,6 +306,9 @@ describe("Execute Ek256", () => {
             },
           },
         },
+        fee: null,
+        relayer: null,
+        replay_protection: { Sequential: {} },
       };
 
       await executeEk256({
@@ -262,6 +334,9 @@ describe("Execute Ek256", () => {
             },
           },
         },
+        fee: null,
+        relayer: null,
+        replay_protection: { Sequential: {} },
       };
 
       await executeEk256({
@@ -309,6 +384,9 @@ describe("Execute Ek256", () => {
           },
         },
       },
+      fee: null,
+      relayer: null,
+      replay_protection: { Sequential: {} },
     };
 
     await executeEk256({
@@ -333,6 +411,9 @@ describe("Execute Ek256", () => {
           },
         },
       },
+      fee: null,
+      relayer: null,
+      replay_protection: { Sequential: {} },
     };
 
     await executeEk256({
@@ -360,39 +441,527 @@ describe("Execute Ek256", () => {
     await confirmTransaction(provider.connection, signature);
 
     const [accountPDA] = findAbstractAccountPDA(accountId, program.programId);
-    const accountInfo = await program.account.abstractAccount.fetch(accountPDA);
+    const accountRent = await connection.getBalance(accountPDA);
+    const refundTo = anchor.web3.Keypair.generate().publicKey;
+
+    await executeEk256Action(program, {
+      accountId: 0n,
+      ethPrivateKey: PRIVATE_KEY,
+      action: { RemoveAccount: { refund_to: refundTo.toBytes() } },
+      remainingAccounts: [
+        { pubkey: refundTo, isSigner: false, isWritable: true },
+      ],
+    });
 
-    const balanceBeforeRemove = await connection.getBalance(
-      provider.wallet.publicKey
+    assert.equal(
+      await connection.getBalance(refundTo),
+      accountRent,
+      "refund_to should receive the account's rent, not the relayer"
     );
+    assert.isNull(
+      await connection.getAccountInfo(accountPDA),
+      "Account should have been deleted"
+    );
+  });
 
-    const transaction: Transaction = {
-      account_id: BigInt(accountId.toString()),
-      nonce: BigInt(accountInfo.nonce.toString()),
+  it("rejects RemoveAccount refunding to an account other than refund_to", async () => {
+    const account = privateKeyToAccount(PRIVATE_KEY);
+    const signature = await program.methods
+      .createAccount(buildEthereumIdentity(account.address, null))
+      .rpc();
+    await confirmTransaction(provider.connection, signature);
+
+    try {
+      await executeEk256Action(program, {
+        accountId: 0n,
+        ethPrivateKey: PRIVATE_KEY,
+        action: {
+          RemoveAccount: {
+            refund_to: anchor.web3.Keypair.generate().publicKey.toBytes(),
+          },
+        },
+        remainingAccounts: [
+          {
+            pubkey: provider.wallet.publicKey,
+            isSigner: false,
+            isWritable: true,
+          },
+        ],
+      });
+      assert.fail("Expected the relayer not to receive the refund");
+    } catch (error: any) {
+      assert.include(error.toString(), "InvalidRefundAccount");
+    }
+  });
+
+  it("pays identity rent from the account under the Account rent payer", async () => {
+    const account = privateKeyToAccount(PRIVATE_KEY);
+    const signature = await program.methods
+      .createAccount(buildEthereumIdentity(account.address, null))
+      .rpc();
+    await confirmTransaction(provider.connection, signature);
+    const [accountPDA] = findAbstractAccountPDA(new BN(0), program.programId);
+
+    await executeEk256Action(program, {
+      accountId: 0n,
+      ethPrivateKey: PRIVATE_KEY,
+      action: { SetRentPayer: { Account: {} } },
+    });
+    // Anyone may fund the account; its identities then own the lamports.
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: provider.wallet.publicKey,
+          toPubkey: accountPDA,
+          lamports: anchor.web3.LAMPORTS_PER_SOL / 100,
+        })
+      )
+    );
+    const fundedBalance = await connection.getBalance(accountPDA);
+
+    await executeEk256Action(program, {
+      accountId: 0n,
+      ethPrivateKey: PRIVATE_KEY,
       action: {
-        RemoveAccount: {},
+        AddIdentity: {
+          identity: { Wallet: { Ethereum: toBytes(ETH_ADDRESS_2) } },
+          permissions: null,
+        },
       },
-    };
+    });
+    assert.equal(
+      await connection.getBalance(accountPDA),
+      fundedBalance,
+      "The account should pay for its growth out of its own balance"
+    );
 
-    await executeEk256({
-      transaction,
-      privateKey: PRIVATE_KEY,
-      accountId: BigInt(accountId.toString()),
+    await executeEk256Action(program, {
+      accountId: 0n,
+      ethPrivateKey: PRIVATE_KEY,
+      action: {
+        RemoveIdentity: { Wallet: { Ethereum: toBytes(ETH_ADDRESS_2) } },
+      },
+    });
+    assert.equal(
+      await connection.getBalance(accountPDA),
+      fundedBalance,
+      "Freed rent should stay with the account, not the relayer"
+    );
+  });
+
+  it("rejects growth the account cannot pay for under the Account rent payer", async () => {
+    const account = privateKeyToAccount(PRIVATE_KEY);
+    const signature = await program.methods
+      .createAccount(buildEthereumIdentity(account.address, null))
+      .rpc();
+    await confirmTransaction(provider.connection, signature);
+
+    await executeEk256Action(program, {
+      accountId: 0n,
+      ethPrivateKey: PRIVATE_KEY,
+      action: { SetRentPayer: { Account: {} } },
     });
 
-    const balanceAfterRemove = await connection.getBalance(
-      provider.wallet.publicKey
+    try {
+      await executeEk256Action(program, {
+        accountId: 0n,
+        ethPrivateKey: PRIVATE_KEY,
+        action: {
+          AddIdentity: {
+            identity: { Wallet: { Ethereum: toBytes(ETH_ADDRESS_2) } },
+            permissions: null,
+          },
+        },
+      });
+      assert.fail("Expected an unfunded account not to grow");
+    } catch (error: any) {
+      assert.include(error.toString(), "InsufficientAccountLamports");
+    }
+  });
+
+  it("pays a signed fee from the account to its recipient", async () => {
+    const account = privateKeyToAccount(PRIVATE_KEY);
+    const signature = await program.methods
+      .createAccount(buildEthereumIdentity(account.address, null))
+      .rpc();
+    await confirmTransaction(provider.connection, signature);
+    const [accountPDA] = findAbstractAccountPDA(new BN(0), program.programId);
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: provider.wallet.publicKey,
+          toPubkey: accountPDA,
+          lamports: anchor.web3.LAMPORTS_PER_SOL / 100,
+        })
+      )
     );
-    assert.isAbove(
-      balanceAfterRemove,
-      balanceBeforeRemove,
-      "Signer balance should increase after removing account due to rent refund"
+
+    const feeRecipient = anchor.web3.Keypair.generate().publicKey;
+    const fee = anchor.web3.LAMPORTS_PER_SOL / 1_000;
+    await executeEk256Action(program, {
+      accountId: 0n,
+      ethPrivateKey: PRIVATE_KEY,
+      action: { SetRentPayer: { Account: {} } },
+      fee: { recipient: feeRecipient.toBytes(), lamports: BigInt(fee) },
+      feeRecipient,
+    });
+
+    assert.equal(await connection.getBalance(feeRecipient), fee);
+  });
+
+  it("rejects a fee whose recipient is not the fee_recipient account", async () => {
+    const account = privateKeyToAccount(PRIVATE_KEY);
+    const signature = await program.methods
+      .createAccount(buildEthereumIdentity(account.address, null))
+      .rpc();
+    await confirmTransaction(provider.connection, signature);
+    const feeRecipient = anchor.web3.Keypair.generate().publicKey;
+
+    try {
+      // Remaining accounts belong to the action and are not searched.
+      await executeEk256Action(program, {
+        accountId: 0n,
+        ethPrivateKey: PRIVATE_KEY,
+        action: { SetRentPayer: { Account: {} } },
+        fee: { recipient: feeRecipient.toBytes(), lamports: 1_000n },
+        remainingAccounts: [
+          { pubkey: feeRecipient, isSigner: false, isWritable: true },
+        ],
+      });
+      assert.fail("Expected a fee without its recipient to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "FeeRecipientMissing");
+    }
+  });
+
+  it("rejects a fee on RemoveAccount", async () => {
+    const account = privateKeyToAccount(PRIVATE_KEY);
+    const signature = await program.methods
+      .createAccount(buildEthereumIdentity(account.address, null))
+      .rpc();
+    await confirmTransaction(provider.connection, signature);
+    const refundTo = anchor.web3.Keypair.generate().publicKey;
+
+    try {
+      await executeEk256Action(program, {
+        accountId: 0n,
+        ethPrivateKey: PRIVATE_KEY,
+        action: { RemoveAccount: { refund_to: refundTo.toBytes() } },
+        fee: {
+          recipient: provider.wallet.publicKey.toBytes(),
+          lamports: 1_000n,
+        },
+        remainingAccounts: [
+          { pubkey: refundTo, isSigner: false, isWritable: true },
+        ],
+      });
+      assert.fail("Expected a fee on a closing account to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "FeeOnRemoveAccount");
+    }
+  });
+
+  it("executes a transaction pinned to the submitting relayer", async () => {
+    const account = privateKeyToAccount(PRIVATE_KEY);
+    const signature = await program.methods
+      .createAccount(buildEthereumIdentity(account.address, null))
+      .rpc();
+    await confirmTransaction(provider.connection, signature);
+
+    await executeEk256Action(program, {
+      accountId: 0n,
+      ethPrivateKey: PRIVATE_KEY,
+      action: { SetRentPayer: { Account: {} } },
+      relayer: provider.wallet.publicKey,
+    });
+
+    const [accountPDA] = findAbstractAccountPDA(new BN(0), program.programId);
+    const accountInfo = await program.account.abstractAccount.fetch(accountPDA);
+    assert.deepEqual(accountInfo.rentPayer, { account: {} });
+  });
+
+  it("rejects a transaction pinned to another relayer", async () => {
+    const account = privateKeyToAccount(PRIVATE_KEY);
+    const signature = await program.methods
+      .createAccount(buildEthereumIdentity(account.address, null))
+      .rpc();
+    await confirmTransaction(provider.connection, signature);
+
+    try {
+      await executeEk256Action(program, {
+        accountId: 0n,
+        ethPrivateKey: PRIVATE_KEY,
+        action: { SetRentPayer: { Account: {} } },
+        relayer: anchor.web3.Keypair.generate().publicKey,
+      });
+      assert.fail("Expected a transaction pinned elsewhere to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "RelayerMismatch");
+    }
+  });
+
+  it("runs transactions on independent nonce keys", async () => {
+    const account = privateKeyToAccount(PRIVATE_KEY);
+    const signature = await program.methods
+      .createAccount(buildEthereumIdentity(account.address, null))
+      .rpc();
+    await confirmTransaction(provider.connection, signature);
+
+    // Key 2 opens without waiting on key 1, and neither moves key 0.
+    for (const nonceKey of [1n, 2n, 1n]) {
+      await executeEk256Action(program, {
+        accountId: 0n,
+        ethPrivateKey: PRIVATE_KEY,
+        action: { SetRentPayer: { Relayer: {} } },
+        nonceKey,
+      });
+    }
+
+    const [accountPDA] = findAbstractAccountPDA(new BN(0), program.programId);
+    const accountInfo = await program.account.abstractAccount.fetch(accountPDA);
+    assert.equal(accountInfo.nonce.toString(), "0");
+    assert.deepEqual(
+      accountInfo.nonceKeys.map(({ key, sequence }) => [
+        key.toString(),
+        sequence.toString(),
+      ]),
+      [
+        ["1", "2"],
+        ["2", "1"],
+      ]
     );
+  });
+
+  it("rejects a new nonce key that does not start at sequence 0", async () => {
+    const account = privateKeyToAccount(PRIVATE_KEY);
+    const signature = await program.methods
+      .createAccount(buildEthereumIdentity(account.address, null))
+      .rpc();
+    await confirmTransaction(provider.connection, signature);
 
     try {
-      await program.account.abstractAccount.fetch(accountPDA);
-      assert.fail("Account should have been deleted");
-    } catch (error) {}
+      await executeEk256Action(program, {
+        accountId: 0n,
+        ethPrivateKey: PRIVATE_KEY,
+        action: { SetRentPayer: { Relayer: {} } },
+        nonce: keyedNonce(1n, 1n),
+      });
+      assert.fail("Expected a skipped sequence to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "NonceMismatch");
+    }
+  });
+
+  it("accepts unordered nonces in any order, each once", async () => {
+    const account = privateKeyToAccount(PRIVATE_KEY);
+    const signature = await program.methods
+      .createAccount(buildEthereumIdentity(account.address, null))
+      .rpc();
+    await confirmTransaction(provider.connection, signature);
+    await program.methods.initNonceBitmap(new BN(0)).rpc();
+
+    const [nonceBitmap] = findNonceBitmapPDA(new BN(0), program.programId);
+    const expirySlot = BigInt((await connection.getSlot()) + 1_000);
+    const executeUnordered = (nonce: bigint) =>
+      executeEk256Action(program, {
+        accountId: 0n,
+        ethPrivateKey: PRIVATE_KEY,
+        action: { SetRentPayer: { Relayer: {} } },
+        nonce,
+        replayProtection: { Unordered: { expiry_slot: expirySlot } },
+        nonceBitmap,
+      });
+
+    await executeUnordered(5n);
+    await executeUnordered(2n);
+
+    const [accountPDA] = findAbstractAccountPDA(new BN(0), program.programId);
+    const accountInfo = await program.account.abstractAccount.fetch(accountPDA);
+    assert.equal(accountInfo.nonce.toString(), "0");
+
+    try {
+      await executeUnordered(5n);
+      assert.fail("Expected a used unordered nonce to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "NonceAlreadyUsed");
+    }
+  });
+
+  it("rejects an unordered transaction past its expiry slot", async () => {
+    const account = privateKeyToAccount(PRIVATE_KEY);
+    const signature = await program.methods
+      .createAccount(buildEthereumIdentity(account.address, null))
+      .rpc();
+    await confirmTransaction(provider.connection, signature);
+    await program.methods.initNonceBitmap(new BN(0)).rpc();
+
+    const [nonceBitmap] = findNonceBitmapPDA(new BN(0), program.programId);
+    const expirySlot = BigInt((await connection.getSlot()) - 1);
+
+    try {
+      await executeEk256Action(program, {
+        accountId: 0n,
+        ethPrivateKey: PRIVATE_KEY,
+        action: { SetRentPayer: { Relayer: {} } },
+        nonce: 0n,
+        replayProtection: { Unordered: { expiry_slot: expirySlot } },
+        nonceBitmap,
+      });
+      assert.fail("Expected an expired transaction to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "TransactionExpired");
+    }
+  });
+
+  it("rejects an unordered transaction without the nonce bitmap", async () => {
+    const account = privateKeyToAccount(PRIVATE_KEY);
+    const signature = await program.methods
+      .createAccount(buildEthereumIdentity(account.address, null))
+      .rpc();
+    await confirmTransaction(provider.connection, signature);
+
+    const expirySlot = BigInt((await connection.getSlot()) + 1_000);
+
+    try {
+      await executeEk256Action(program, {
+        accountId: 0n,
+        ethPrivateKey: PRIVATE_KEY,
+        action: { SetRentPayer: { Relayer: {} } },
+        nonce: 0n,
+        replayProtection: { Unordered: { expiry_slot: expirySlot } },
+      });
+      assert.fail("Expected a transaction without its bitmap to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "NonceBitmapMissing");
+    }
+  });
+
+  it("bounds how far one unordered nonce slides the window", async () => {
+    const account = privateKeyToAccount(PRIVATE_KEY);
+    const signature = await program.methods
+      .createAccount(buildEthereumIdentity(account.address, null))
+      .rpc();
+    await confirmTransaction(provider.connection, signature);
+    await program.methods.initNonceBitmap(new BN(0)).rpc();
+
+    const [nonceBitmap] = findNonceBitmapPDA(new BN(0), program.programId);
+    const expirySlot = BigInt((await connection.getSlot()) + 1_000);
+    const executeUnordered = (nonce: bigint) =>
+      executeEk256Action(program, {
+        accountId: 0n,
+        ethPrivateKey: PRIVATE_KEY,
+        action: { SetRentPayer: { Relayer: {} } },
+        nonce,
+        replayProtection: { Unordered: { expiry_slot: expirySlot } },
+        nonceBitmap,
+      });
+
+    // WINDOW is 1024 and MAX_STRIDE 256, so nonce 1280 would retire 257.
+    try {
+      await executeUnordered(1280n);
+      assert.fail("Expected a nonce past MAX_STRIDE to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "NonceTooFarAhead");
+    }
+
+    await executeUnordered(1279n);
+    try {
+      await executeUnordered(255n);
+      assert.fail("Expected a retired nonce to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "NonceAlreadyUsed");
+    }
+    await executeUnordered(256n);
+  });
+
+  it("closes a bitmap left behind by its closed account", async () => {
+    const account = privateKeyToAccount(PRIVATE_KEY);
+    const signature = await program.methods
+      .createAccount(buildEthereumIdentity(account.address, null))
+      .rpc();
+    await confirmTransaction(provider.connection, signature);
+    await program.methods.initNonceBitmap(new BN(0)).rpc();
+    const [nonceBitmap] = findNonceBitmapPDA(new BN(0), program.programId);
+
+    const closeNonceBitmap = () =>
+      program.methods
+        .closeNonceBitmap(new BN(0))
+        .accountsPartial({ payer: provider.wallet.publicKey })
+        .rpc();
+
+    try {
+      await closeNonceBitmap();
+      assert.fail("Expected the bitmap of an open account to stay");
+    } catch (error: any) {
+      assert.include(error.toString(), "AccountStillOpen");
+    }
+
+    const refundTo = anchor.web3.Keypair.generate().publicKey;
+    await executeEk256Action(program, {
+      accountId: 0n,
+      ethPrivateKey: PRIVATE_KEY,
+      action: { RemoveAccount: { refund_to: refundTo.toBytes() } },
+      remainingAccounts: [
+        { pubkey: refundTo, isSigner: false, isWritable: true },
+      ],
+    });
+    assert.isNotNull(await connection.getAccountInfo(nonceBitmap));
+
+    await confirmTransaction(connection, await closeNonceBitmap());
+    assert.isNull(await connection.getAccountInfo(nonceBitmap));
+  });
+
+  it("refunds a removed account's bitmap to the payer that funded it", async () => {
+    const account = privateKeyToAccount(PRIVATE_KEY);
+    const signature = await program.methods
+      .createAccount(buildEthereumIdentity(account.address, null))
+      .rpc();
+    await confirmTransaction(provider.connection, signature);
+
+    // Someone other than the relayer funds the bitmap.
+    const funder = anchor.web3.Keypair.generate();
+    const airdrop = await connection.requestAirdrop(
+      funder.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    await confirmTransaction(connection, airdrop);
+    const initSignature = await program.methods
+      .initNonceBitmap(new BN(0))
+      .accountsPartial({ signer: funder.publicKey })
+      .signers([funder])
+      .rpc();
+    await confirmTransaction(connection, initSignature);
+
+    const [nonceBitmap] = findNonceBitmapPDA(new BN(0), program.programId);
+    const bitmapRent = await connection.getBalance(nonceBitmap);
+    const funderBalance = await connection.getBalance(funder.publicKey);
+    const refundTo = anchor.web3.Keypair.generate().publicKey;
+    const removeAccount = (nonceBitmapPayer: anchor.web3.PublicKey) =>
+      executeEk256Action(program, {
+        accountId: 0n,
+        ethPrivateKey: PRIVATE_KEY,
+        action: { RemoveAccount: { refund_to: refundTo.toBytes() } },
+        remainingAccounts: [
+          { pubkey: refundTo, isSigner: false, isWritable: true },
+        ],
+        nonceBitmap,
+        nonceBitmapPayer,
+      });
+
+    try {
+      await removeAccount(provider.wallet.publicKey);
+      assert.fail("Expected the bitmap rent not to go to the relayer");
+    } catch (error: any) {
+      assert.include(error.toString(), "NonceBitmapPayerMismatch");
+    }
+
+    await removeAccount(funder.publicKey);
+    assert.isNull(await connection.getAccountInfo(nonceBitmap));
+    assert.equal(
+      await connection.getBalance(funder.publicKey),
+      funderBalance + bitmapRent,
+      "The funder should get the bitmap's rent back"
+    );
   });
 
   it("should create multiple accounts and verify they can be managed independently", async () => {
@@ -447,6 +1016,9 @@ describe("Execute Ek256", () => {
             },
           },
         },
+        fee: null,
+        relayer: null,
+        replay_protection: { Sequential: {} },
       };
 
       await executeEk256({
@@ -492,6 +1064,9 @@ describe("Execute Ek256", () => {
           },
         },
       },
+      fee: null,
+      relayer: null,
+      replay_protection: { Sequential: {} },
     };
 
     try {
@@ -538,6 +1113,9 @@ describe("Execute Ek256", () => {
           },
         },
       },
+      fee: null,
+      relayer: null,
+      replay_protection: { Sequential: {} },
     };
 
     try {
@@ -584,6 +1162,9 @@ describe("Execute Ek256", () => {
           },
         },
       },
+      fee: null,
+      relayer: null,
+      replay_protection: { Sequential: {} },
     };
 
     try {
@@ -601,4 +1182,135 @@ describe("Execute Ek256", () => {
       );
     }
   });
+
+  it("should execute a transaction read from a buffer", async () => {
+    const accountId = new BN(0);
+    const account = privateKeyToAccount(PRIVATE_KEY);
+    const signature = await program.methods
+      .createAccount(buildEthereumIdentity(account.address, null))
+      .rpc();
+    await confirmTransaction(provider.connection, signature);
+
+    const [accountPDA] = findAbstractAccountPDA(accountId, program.programId);
+    const accountInfo = await program.account.abstractAccount.fetch(accountPDA);
+
+    const transaction: Transaction = {
+      account_id: BigInt(accountId.toString()),
+      nonce: BigInt(accountInfo.nonce.toString()),
+      action: {
+        AddIdentity: {
+          identity: { Wallet: { Ethereum: toBytes(ETH_ADDRESS_2) } },
+          permissions: { enable_act_as: true },
+        },
+      },
+      fee: null,
+      relayer: null,
+      replay_protection: { Sequential: {} },
+    };
+
+    const { unifiedStorage } = await executeEk256FromBuffer({
+      transaction,
+      privateKey: PRIVATE_KEY,
+      accountId: BigInt(accountId.toString()),
+    });
+
+    const accountInfoUpdated = await program.account.abstractAccount.fetch(
+      accountPDA
+    );
+    assert.equal(accountInfoUpdated.nonce.toString(), "1");
+    assert.equal(accountInfoUpdated.identities.length, 2);
+    assert.isNull(
+      await provider.connection.getAccountInfo(unifiedStorage),
+      "Buffer should be closed to its owner"
+    );
+  });
+
+  it("should let a relayer execute a user's buffer and refund the owner", async () => {
+    const accountId = new BN(0);
+    const account = privateKeyToAccount(PRIVATE_KEY);
+    const signature = await program.methods
+      .createAccount(buildEthereumIdentity(account.address, null))
+      .rpc();
+    await confirmTransaction(provider.connection, signature);
+
+    const [accountPDA] = findAbstractAccountPDA(accountId, program.programId);
+    const transaction: Transaction = {
+      account_id: BigInt(accountId.toString()),
+      nonce: 0n,
+      action: {
+        AddIdentity: {
+          identity: { Wallet: { Ethereum: toBytes(ETH_ADDRESS_2) } },
+          permissions: { enable_act_as: true },
+        },
+      },
+      fee: null,
+      relayer: null,
+      replay_protection: { Sequential: {} },
+    };
+    const relayer = anchor.web3.Keypair.generate();
+
+    try {
+      await executeEk256FromBuffer({
+        transaction,
+        privateKey: PRIVATE_KEY,
+        accountId: BigInt(accountId.toString()),
+        relayer,
+        owner: relayer.publicKey,
+      });
+      assert.fail("Transaction should have failed due to the wrong owner");
+    } catch (error: any) {
+      assert.include(error.toString(), "BufferOwnerMismatch");
+    }
+
+    const { unifiedStorage } = await executeEk256FromBuffer({
+      transaction,
+      privateKey: PRIVATE_KEY,
+      accountId: BigInt(accountId.toString()),
+      relayer,
+    });
+
+    const accountInfo = await program.account.abstractAccount.fetch(accountPDA);
+    assert.equal(accountInfo.nonce.toString(), "1");
+    assert.isNull(await provider.connection.getAccountInfo(unifiedStorage));
+    // The rent went to the wallet that owns the buffer, not to the relayer.
+    assert.equal(await provider.connection.getBalance(relayer.publicKey), 0);
+  });
+
+  it("should fail when the signed digest is not of the buffered transaction", async () => {
+    const accountId = new BN(0);
+    const account = privateKeyToAccount(PRIVATE_KEY);
+    const signature = await program.methods
+      .createAccount(buildEthereumIdentity(account.address, null))
+      .rpc();
+    await confirmTransaction(provider.connection, signature);
+
+    const [accountPDA] = findAbstractAccountPDA(accountId, program.programId);
+    const accountInfo = await program.account.abstractAccount.fetch(accountPDA);
+
+    const addIdentity = (address: Hex): Transaction => ({
+      account_id: BigInt(accountId.toString()),
+      nonce: BigInt(accountInfo.nonce.toString()),
+      action: {
+        AddIdentity: {
+          identity: { Wallet: { Ethereum: toBytes(address) } },
+          permissions: { enable_act_as: true },
+        },
+      },
+      fee: null,
+      relayer: null,
+      replay_protection: { Sequential: {} },
+    });
+
+    try {
+      await executeEk256FromBuffer({
+        transaction: addIdentity(ETH_ADDRESS_3),
+        signedTransaction: addIdentity(ETH_ADDRESS_2),
+        privateKey: PRIVATE_KEY,
+        accountId: BigInt(accountId.toString()),
+      });
+      assert.fail("Transaction should have failed due to a digest mismatch");
+    } catch (error: any) {
+      assert.include(error.toString(), "TransactionDigestMismatch");
+    }
+  });
 });
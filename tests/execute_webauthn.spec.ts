@@ -10,6 +10,10 @@ import { SolanaAa } from "../target/types/solana_aa";
 import { buildWebauthnIdentity } from "../utils/identity";
 import { cleanUpProgramState, findAbstractAccountPDA } from "../utils/program";
 import { confirmTransaction } from "../utils/solana";
+import {
+  findUnifiedStoragePDA,
+  writeTransactionBuffer,
+} from "../utils/transaction-buffer";
 import { createSecp256r1VerificationInstruction } from "../utils/webauthn";
 
 // Deterministic P-256 test credential.
@@ -56,6 +60,9 @@ describe("Execute WebAuthn", () => {
           permissions: { enable_act_as: false },
         },
       },
+      fee: null,
+      relayer: null,
+      replay_protection: { Sequential: {} },
     };
     const arg: Parameters<typeof program.methods.executeWebauthn>[1] = {
       accountId: new BN(accountId.toString()),
@@ -72,6 +79,9 @@ describe("Execute WebAuthn", () => {
           },
         },
       },
+      fee: null,
+      relayer: null,
+      replayProtection: { sequential: {} },
     };
     return { borsh, arg };
   };
@@ -154,6 +164,23 @@ describe("Execute WebAuthn", () => {
       ])
       .rpc();
 
+  // `executeWebauthn` with its arguments uploaded to a transaction buffer.
+  const executeWebauthnFromBuffer = (
+    accountId: bigint,
+    unifiedStorage: anchor.web3.PublicKey,
+    verificationIx: TransactionInstruction
+  ) =>
+    program.methods
+      .executeWebauthnFromBuffer(new BN(accountId.toString()))
+      .accountsPartial({ unifiedStorage, owner: provider.wallet.publicKey })
+      .preInstructions([
+        ComputeBudgetProgram.setComputeUnitLimit({
+          units: WEBAUTHN_COMPUTE_UNITS,
+        }),
+        verificationIx,
+      ])
+      .rpc();
+
   it("executes an AddIdentity transaction authorized by a WebAuthn signature", async () => {
     const { pda, nonce } = await createWebauthnAccount();
     const tx = addIdentityTx(0n, nonce);
@@ -305,4 +332,81 @@ describe("Execute WebAuthn", () => {
       assert.include(error.toString(), "AccountIdMismatch");
     }
   });
+
+  it("executes a transaction whose arguments are read from a buffer", async () => {
+    const { pda, nonce } = await createWebauthnAccount();
+    const tx = addIdentityTx(0n, nonce);
+    const signed = signWebauthn(tx.borsh);
+    const unifiedStorage = await writeTransactionBuffer(
+      program,
+      borshUtils.serialize.executeWebauthnArgs({
+        transaction: tx.borsh,
+        auth: {
+          client_data: signed.clientData,
+          authenticator_data: signed.authData,
+        },
+      })
+    );
+
+    const sig = await executeWebauthnFromBuffer(
+      0n,
+      unifiedStorage,
+      signed.verificationIx
+    );
+    await confirmTransaction(connection, sig);
+
+    const info = await program.account.abstractAccount.fetch(pda);
+    assert.strictEqual(info.nonce.toString(), "1");
+    assert.strictEqual(info.identities.length, 2);
+    // The buffer is consumed: closed and its rent returned to its owner.
+    assert.isNull(await connection.getAccountInfo(unifiedStorage));
+  });
+
+  it("rejects executing from a buffer that is not finalized", async () => {
+    const { nonce } = await createWebauthnAccount();
+    const tx = addIdentityTx(0n, nonce);
+    const signed = signWebauthn(tx.borsh);
+    const payload = Buffer.from(
+      borshUtils.serialize.executeWebauthnArgs({
+        transaction: tx.borsh,
+        auth: {
+          client_data: signed.clientData,
+          authenticator_data: signed.authData,
+        },
+      })
+    );
+    // The buffer is allocated, but never filled and finalized.
+    const dataId = Array.from(Buffer.alloc(32, 0x39));
+    const initSig = await program.methods
+      .initStorage(dataId, [], payload.length, Array.from(sha256(payload)))
+      .accounts({
+        owner: provider.wallet.publicKey,
+        payer: provider.wallet.publicKey,
+      })
+      .rpc();
+    await confirmTransaction(connection, initSig);
+    const [unifiedStorage] = findUnifiedStoragePDA(
+      provider.wallet.publicKey,
+      Uint8Array.from(dataId),
+      program.programId
+    );
+
+    try {
+      await executeWebauthnFromBuffer(
+        0n,
+        unifiedStorage,
+        signed.verificationIx
+      );
+      assert.fail(
+        "executeWebauthnFromBuffer resolved but a rejection was expected"
+      );
+    } catch (error: any) {
+      assert.include(error.toString(), "StorageNotFinalized");
+    } finally {
+      await program.methods
+        .closeStorage()
+        .accountsPartial({ unifiedStorage, owner: provider.wallet.publicKey })
+        .rpc();
+    }
+  });
 });
@@ -0,0 +1,442 @@
+import * as anchor from "@coral-xyz/anchor";
+import { BN } from "@coral-xyz/anchor";
+import { Keypair } from "@solana/web3.js";
+import { assert } from "chai";
+import { SolanaAa } from "../target/types/solana_aa";
+import { buildEthereumIdentity } from "../utils/identity";
+import { cleanUpProgramState, findAbstractAccountPDA } from "../utils/program";
+import { confirmTransaction } from "../utils/solana";
+import { executeEk256Action } from "../utils/test-helpers";
+import { findOidcKeyRegistryPDA, oidcIssuerHash } from "../utils/zk-oidc";
+
+const ETH_PRIVATE_KEY =
+  "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80" as const;
+const ETH_ADDRESS = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+
+// A registry of its own, so governance changes never leak into the ZK OIDC
+// execution tests.
+const ISS = "https://governance.example.com";
+const CLOCK_SKEW = new BN(60);
+const KEY_ADD_DELAY = 60 * 60;
+
+describe("OIDC Registry Governance", () => {
+  const provider = anchor.getProvider() as anchor.AnchorProvider;
+  const program = anchor.workspace.solanaAa as anchor.Program<SolanaAa>;
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const connection = anchor.getProvider().connection;
+
+  const [oidcKeyRegistry] = findOidcKeyRegistryPDA(ISS, program.programId);
+
+  // Co-signers only approve; the provider wallet signs as `authority` and
+  // pays, so they never need funding.
+  const coSignerA = Keypair.generate();
+  const coSignerB = Keypair.generate();
+
+  const keyEntry = {
+    pkHash: Array.from(Buffer.alloc(32, 0x11)),
+    keyType: { rsa: {} },
+    kid: "governance-key-1",
+    notBefore: new BN(0),
+    notAfter: new BN("18446744073709551615"),
+  };
+
+  const asSigners = (keypairs: Keypair[]) =>
+    keypairs.map((keypair) => ({
+      pubkey: keypair.publicKey,
+      isSigner: true,
+      isWritable: false,
+    }));
+
+  // Adds the key approved by the wallet plus `coSigners`.
+  const addKey = async (coSigners: Keypair[] = []) => {
+    const signature = await program.methods
+      .addOidcKey(keyEntry)
+      .accountsPartial({ oidcKeyRegistry })
+      .remainingAccounts(asSigners(coSigners))
+      .signers(coSigners)
+      .rpc();
+    await confirmTransaction(connection, signature);
+  };
+
+  // Moves the registry to a 2-of-3 set of the wallet and both co-signers.
+  const transferToMultisig = async () => {
+    const proposeSignature = await program.methods
+      .proposeOidcAuthorities(
+        [provider.wallet.publicKey, coSignerA.publicKey, coSignerB.publicKey],
+        2
+      )
+      .accountsPartial({ oidcKeyRegistry })
+      .rpc();
+    await confirmTransaction(connection, proposeSignature);
+
+    const acceptSignature = await program.methods
+      .acceptOidcAuthorities()
+      .accountsPartial({ oidcKeyRegistry })
+      .remainingAccounts(asSigners([coSignerA]))
+      .signers([coSignerA])
+      .rpc();
+    await confirmTransaction(connection, acceptSignature);
+  };
+
+  const resetRegistry = async (keyAddDelay: number) => {
+    // Both co-signers approve, which satisfies either the 1-of-1 or the 2-of-3
+    // set a previous test left behind. Only an empty registry can be closed.
+    try {
+      await program.methods
+        .removeOidcKey(keyEntry.pkHash)
+        .accountsPartial({ oidcKeyRegistry })
+        .remainingAccounts(asSigners([coSignerA, coSignerB]))
+        .signers([coSignerA, coSignerB])
+        .rpc();
+    } catch {
+      // Registry or key did not exist.
+    }
+    try {
+      await program.methods
+        .closeOidcRegistry()
+        .accountsPartial({
+          authority: provider.wallet.publicKey,
+          oidcKeyRegistry,
+        })
+        .remainingAccounts(asSigners([coSignerA, coSignerB]))
+        .signers([coSignerA, coSignerB])
+        .rpc();
+    } catch {
+      // Registry did not exist yet.
+    }
+
+    const signature = await program.methods
+      .initOidcRegistry(
+        Array.from(oidcIssuerHash(ISS)),
+        ISS,
+        CLOCK_SKEW,
+        { raw: {} },
+        new BN(keyAddDelay)
+      )
+      .rpc();
+    await confirmTransaction(connection, signature);
+  };
+
+  beforeEach(async () => {
+    await resetRegistry(0);
+  });
+
+  it("starts as a 1-of-1 registry governed by its creator", async () => {
+    const registry = await program.account.oidcKeyRegistry.fetch(
+      oidcKeyRegistry
+    );
+
+    assert.deepEqual(
+      registry.authorities.map((key) => key.toBase58()),
+      [provider.wallet.publicKey.toBase58()]
+    );
+    assert.strictEqual(registry.threshold, 1);
+    assert.strictEqual(registry.pendingAuthorities.length, 0);
+  });
+
+  it("lets only the deployment admin create a registry", async () => {
+    const intruder = Keypair.generate();
+    const airdrop = await connection.requestAirdrop(
+      intruder.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    await confirmTransaction(connection, airdrop);
+    const iss = "https://unclaimed.example.com";
+
+    try {
+      await program.methods
+        .initOidcRegistry(
+          Array.from(oidcIssuerHash(iss)),
+          iss,
+          CLOCK_SKEW,
+          { raw: {} },
+          new BN(0)
+        )
+        .accounts({ authority: intruder.publicKey })
+        .signers([intruder])
+        .rpc();
+      assert.fail("Expected a non-admin to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "NotAdmin");
+    }
+  });
+
+  it("transfers authority only once the proposed set accepts", async () => {
+    await transferToMultisig();
+
+    const registry = await program.account.oidcKeyRegistry.fetch(
+      oidcKeyRegistry
+    );
+    assert.strictEqual(registry.authorities.length, 3);
+    assert.strictEqual(registry.threshold, 2);
+    assert.strictEqual(registry.pendingAuthorities.length, 0);
+  });
+
+  it("rejects an acceptance below the proposed threshold", async () => {
+    const proposeSignature = await program.methods
+      .proposeOidcAuthorities([coSignerA.publicKey, coSignerB.publicKey], 2)
+      .accountsPartial({ oidcKeyRegistry })
+      .rpc();
+    await confirmTransaction(connection, proposeSignature);
+
+    try {
+      await program.methods
+        .acceptOidcAuthorities()
+        .accountsPartial({ oidcKeyRegistry })
+        .remainingAccounts(asSigners([coSignerA]))
+        .signers([coSignerA])
+        .rpc();
+      assert.fail("Expected a 1-of-2 acceptance to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "InsufficientApprovals");
+    }
+  });
+
+  it("rejects an authority set with duplicate keys", async () => {
+    try {
+      await program.methods
+        .proposeOidcAuthorities([coSignerA.publicKey, coSignerA.publicKey], 2)
+        .accountsPartial({ oidcKeyRegistry })
+        .rpc();
+      assert.fail("Expected a duplicate authority to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "InvalidAuthoritySet");
+    }
+  });
+
+  it("requires the threshold of authorities to change keys", async () => {
+    await transferToMultisig();
+
+    try {
+      await addKey();
+      assert.fail("Expected a single approval to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "InsufficientApprovals");
+    }
+
+    await addKey([coSignerB]);
+
+    const registry = await program.account.oidcKeyRegistry.fetch(
+      oidcKeyRegistry
+    );
+    assert.strictEqual(registry.keys.length, 1);
+  });
+
+  it("rejects key changes from a signer outside the authority set", async () => {
+    const intruder = Keypair.generate();
+    const airdrop = await connection.requestAirdrop(
+      intruder.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    await confirmTransaction(connection, airdrop);
+
+    try {
+      await program.methods
+        .addOidcKey(keyEntry)
+        .accountsPartial({ authority: intruder.publicKey, oidcKeyRegistry })
+        .signers([intruder])
+        .rpc();
+      assert.fail("Expected a non-authority signer to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "InsufficientApprovals");
+    }
+  });
+
+  it("delays new keys by the registry key-add delay", async () => {
+    await resetRegistry(KEY_ADD_DELAY);
+
+    const slot = await connection.getSlot();
+    const now = await connection.getBlockTime(slot);
+    await addKey();
+
+    const registry = await program.account.oidcKeyRegistry.fetch(
+      oidcKeyRegistry
+    );
+    assert.isAtLeast(
+      registry.keys[0].notBefore.toNumber(),
+      now! + KEY_ADD_DELAY
+    );
+  });
+
+  it("removes keys immediately, without the delay", async () => {
+    await resetRegistry(KEY_ADD_DELAY);
+    await addKey();
+
+    const signature = await program.methods
+      .removeOidcKey(keyEntry.pkHash)
+      .accountsPartial({ oidcKeyRegistry })
+      .rpc();
+    await confirmTransaction(connection, signature);
+
+    const registry = await program.account.oidcKeyRegistry.fetch(
+      oidcKeyRegistry
+    );
+    assert.strictEqual(registry.keys.length, 0);
+  });
+
+  it("refuses to close a registry that still holds keys", async () => {
+    await addKey();
+
+    try {
+      await program.methods
+        .closeOidcRegistry()
+        .accountsPartial({
+          authority: provider.wallet.publicKey,
+          oidcKeyRegistry,
+        })
+        .rpc();
+      assert.fail("Expected a registry with keys to stay open");
+    } catch (error: any) {
+      assert.include(error.toString(), "RegistryNotEmpty");
+    }
+  });
+
+  it("rejects lowering the key-add delay", async () => {
+    await resetRegistry(KEY_ADD_DELAY);
+
+    try {
+      await program.methods
+        .setOidcKeyAddDelay(new BN(KEY_ADD_DELAY - 1))
+        .accountsPartial({ oidcKeyRegistry })
+        .rpc();
+      assert.fail("Expected a lower key-add delay to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "KeyAddDelayDecrease");
+    }
+  });
+
+  it("lets an abstract account govern the registry through actions", async () => {
+    await cleanUpProgramState(program, connection, provider);
+    const createSignature = await program.methods
+      .createAccount(buildEthereumIdentity(ETH_ADDRESS, null))
+      .rpc();
+    await confirmTransaction(connection, createSignature);
+    const [accountPDA] = findAbstractAccountPDA(new BN(0), program.programId);
+
+    // The registry outlives the test, so give it an issuer no other test
+    // uses.
+    const iss = `https://account-governed.example.com/${Date.now()}`;
+    const issHash = oidcIssuerHash(iss);
+    const [registry] = findOidcKeyRegistryPDA(iss, program.programId);
+    const registryAccounts = [
+      { pubkey: registry, isSigner: false, isWritable: true },
+    ];
+
+    const initSignature = await program.methods
+      .initOidcRegistry(
+        Array.from(issHash),
+        iss,
+        CLOCK_SKEW,
+        { raw: {} },
+        new BN(0)
+      )
+      .rpc();
+    await confirmTransaction(connection, initSignature);
+    const proposeSignature = await program.methods
+      .proposeOidcAuthorities([accountPDA], 1)
+      .accountsPartial({ oidcKeyRegistry: registry })
+      .rpc();
+    await confirmTransaction(connection, proposeSignature);
+
+    await executeEk256Action(program, {
+      accountId: 0n,
+      ethPrivateKey: ETH_PRIVATE_KEY,
+      action: { AcceptOidcAuthorities: { iss_hash: issHash } },
+      remainingAccounts: registryAccounts,
+    });
+    await executeEk256Action(program, {
+      accountId: 0n,
+      ethPrivateKey: ETH_PRIVATE_KEY,
+      action: {
+        AddOidcKey: {
+          iss_hash: issHash,
+          key_entry: {
+            pk_hash: Uint8Array.from(keyEntry.pkHash),
+            key_type: { Rsa: {} },
+            kid: keyEntry.kid,
+            not_before: 0n,
+            not_after: BigInt(keyEntry.notAfter.toString()),
+          },
+        },
+      },
+      remainingAccounts: registryAccounts,
+    });
+
+    let registryInfo = await program.account.oidcKeyRegistry.fetch(registry);
+    assert.deepEqual(
+      registryInfo.authorities.map((key) => key.toBase58()),
+      [accountPDA.toBase58()]
+    );
+    assert.strictEqual(registryInfo.keys.length, 1);
+
+    // The wallet that created the registry no longer governs it.
+    try {
+      await program.methods
+        .removeOidcKey(keyEntry.pkHash)
+        .accountsPartial({ oidcKeyRegistry: registry })
+        .rpc();
+      assert.fail("Expected the former authority to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "InsufficientApprovals");
+    }
+
+    await executeEk256Action(program, {
+      accountId: 0n,
+      ethPrivateKey: ETH_PRIVATE_KEY,
+      action: {
+        RemoveOidcKey: {
+          iss_hash: issHash,
+          pk_hash: Uint8Array.from(keyEntry.pkHash),
+        },
+      },
+      remainingAccounts: registryAccounts,
+    });
+
+    registryInfo = await program.account.oidcKeyRegistry.fetch(registry);
+    assert.strictEqual(registryInfo.keys.length, 0);
+
+    // The account can also retune the registry and hand it back.
+    await executeEk256Action(program, {
+      accountId: 0n,
+      ethPrivateKey: ETH_PRIVATE_KEY,
+      action: { SetOidcClockSkew: { iss_hash: issHash, clock_skew: 120n } },
+      remainingAccounts: registryAccounts,
+    });
+    await executeEk256Action(program, {
+      accountId: 0n,
+      ethPrivateKey: ETH_PRIVATE_KEY,
+      action: {
+        SetOidcKeyAddDelay: {
+          iss_hash: issHash,
+          key_add_delay: BigInt(KEY_ADD_DELAY),
+        },
+      },
+      remainingAccounts: registryAccounts,
+    });
+    await executeEk256Action(program, {
+      accountId: 0n,
+      ethPrivateKey: ETH_PRIVATE_KEY,
+      action: {
+        ProposeOidcAuthorities: {
+          iss_hash: issHash,
+          authorities: [provider.wallet.publicKey.toBytes()],
+          threshold: 1,
+        },
+      },
+      remainingAccounts: registryAccounts,
+    });
+    const acceptSignature = await program.methods
+      .acceptOidcAuthorities()
+      .accountsPartial({ oidcKeyRegistry: registry })
+      .rpc();
+    await confirmTransaction(connection, acceptSignature);
+
+    registryInfo = await program.account.oidcKeyRegistry.fetch(registry);
+    assert.strictEqual(registryInfo.clockSkew.toNumber(), 120);
+    assert.strictEqual(registryInfo.keyAddDelay.toNumber(), KEY_ADD_DELAY);
+    assert.deepEqual(
+      registryInfo.authorities.map((key) => key.toBase58()),
+      [provider.wallet.publicKey.toBase58()]
+    );
+  });
+});
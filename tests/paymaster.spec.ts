@@ -0,0 +1,190 @@
+import * as anchor from "@coral-xyz/anchor";
+import { BN } from "@coral-xyz/anchor";
+import { assert } from "chai";
+import { toBytes } from "viem";
+import { SolanaAa } from "../target/types/solana_aa";
+import { buildEthereumIdentity } from "../utils/identity";
+import { cleanUpProgramState, findPaymasterPDA } from "../utils/program";
+import { confirmTransaction } from "../utils/solana";
+import { executeEk256Action } from "../utils/test-helpers";
+
+const ETH_PRIVATE_KEY =
+  "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80" as const;
+const ETH_ADDRESS = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+const ETH_ADDRESS_2 = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8";
+
+const REIMBURSEMENT = 5_000;
+// Two sponsored executions per account per day.
+const DAILY_BUDGET = 2 * REIMBURSEMENT;
+// `Action::mask` bits of AddIdentity (variant 1) and RemoveIdentity
+// (variant 2).
+const ALLOWED_ACTIONS = (1 << 1) | (1 << 2);
+
+describe("Paymaster", () => {
+  const provider = anchor.getProvider() as anchor.AnchorProvider;
+  const program = anchor.workspace.solanaAa as anchor.Program<SolanaAa>;
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const connection = anchor.getProvider().connection;
+
+  // The provider wallet sponsors and relays; the paymaster balance shows what
+  // it was reimbursed.
+  const [paymaster] = findPaymasterPDA(
+    provider.wallet.publicKey,
+    program.programId
+  );
+
+  const addIdentity = {
+    AddIdentity: {
+      identity: { Wallet: { Ethereum: toBytes(ETH_ADDRESS_2) } },
+      permissions: null,
+    },
+  };
+  const removeIdentity = {
+    RemoveIdentity: { Wallet: { Ethereum: toBytes(ETH_ADDRESS_2) } },
+  };
+
+  beforeEach(async () => {
+    await cleanUpProgramState(program, connection, provider);
+
+    const createSignature = await program.methods
+      .createAccount(buildEthereumIdentity(ETH_ADDRESS, null))
+      .rpc();
+    await confirmTransaction(connection, createSignature);
+
+    const initSignature = await program.methods
+      .initPaymaster(
+        new BN(REIMBURSEMENT),
+        new BN(DAILY_BUDGET),
+        ALLOWED_ACTIONS
+      )
+      .rpc();
+    await confirmTransaction(connection, initSignature);
+    const addSignature = await program.methods
+      .addSponsoredAccount(new BN(0))
+      .rpc();
+    await confirmTransaction(connection, addSignature);
+
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: provider.wallet.publicKey,
+          toPubkey: paymaster,
+          lamports: anchor.web3.LAMPORTS_PER_SOL / 10,
+        })
+      )
+    );
+  });
+
+  afterEach(async () => {
+    const signature = await program.methods.closePaymaster().rpc();
+    await confirmTransaction(connection, signature);
+  });
+
+  it("reimburses the relayer for a sponsored action", async () => {
+    const balanceBefore = await connection.getBalance(paymaster);
+
+    await executeEk256Action(program, {
+      accountId: 0n,
+      ethPrivateKey: ETH_PRIVATE_KEY,
+      action: addIdentity,
+      paymaster,
+    });
+
+    assert.equal(
+      await connection.getBalance(paymaster),
+      balanceBefore - REIMBURSEMENT
+    );
+    const paymasterAccount = await program.account.paymaster.fetch(paymaster);
+    assert.equal(paymasterAccount.accounts[0].spent.toNumber(), REIMBURSEMENT);
+  });
+
+  it("rejects an action kind the paymaster does not sponsor", async () => {
+    try {
+      await executeEk256Action(program, {
+        accountId: 0n,
+        ethPrivateKey: ETH_PRIVATE_KEY,
+        action: { SetRentPayer: { Account: {} } },
+        paymaster,
+      });
+      assert.fail("Expected SetRentPayer not to be sponsored");
+    } catch (error: any) {
+      assert.include(error.toString(), "ActionNotSponsored");
+    }
+  });
+
+  it("rejects an account the paymaster does not sponsor", async () => {
+    const signature = await program.methods
+      .removeSponsoredAccount(new BN(0))
+      .rpc();
+    await confirmTransaction(connection, signature);
+
+    try {
+      await executeEk256Action(program, {
+        accountId: 0n,
+        ethPrivateKey: ETH_PRIVATE_KEY,
+        action: addIdentity,
+        paymaster,
+      });
+      assert.fail("Expected account 0 not to be sponsored");
+    } catch (error: any) {
+      assert.include(error.toString(), "AccountNotSponsored");
+    }
+  });
+
+  it("rejects reimbursements beyond the account's daily budget", async () => {
+    await executeEk256Action(program, {
+      accountId: 0n,
+      ethPrivateKey: ETH_PRIVATE_KEY,
+      action: addIdentity,
+      paymaster,
+    });
+    await executeEk256Action(program, {
+      accountId: 0n,
+      ethPrivateKey: ETH_PRIVATE_KEY,
+      action: removeIdentity,
+      paymaster,
+    });
+
+    try {
+      await executeEk256Action(program, {
+        accountId: 0n,
+        ethPrivateKey: ETH_PRIVATE_KEY,
+        action: addIdentity,
+        paymaster,
+      });
+      assert.fail("Expected a third reimbursement in a day to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "DailyBudgetExceeded");
+    }
+
+    // Without the paymaster the relayer simply pays.
+    await executeEk256Action(program, {
+      accountId: 0n,
+      ethPrivateKey: ETH_PRIVATE_KEY,
+      action: addIdentity,
+    });
+  });
+
+  it("lets the sponsor withdraw down to the rent-exempt minimum", async () => {
+    const balance = await connection.getBalance(paymaster);
+    const paymasterInfo = await connection.getAccountInfo(paymaster);
+    const minimum = await connection.getMinimumBalanceForRentExemption(
+      paymasterInfo!.data.length
+    );
+
+    try {
+      await program.methods
+        .withdrawFromPaymaster(new BN(balance - minimum + 1))
+        .rpc();
+      assert.fail("Expected a withdrawal below rent exemption to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "InsufficientPaymasterFunds");
+    }
+
+    const signature = await program.methods
+      .withdrawFromPaymaster(new BN(balance - minimum))
+      .rpc();
+    await confirmTransaction(connection, signature);
+    assert.equal(await connection.getBalance(paymaster), minimum);
+  });
+});
@@ -99,6 +99,9 @@ describe("Execute Sign (chain-signatures CPI)", () => {
           params: "",
         },
       },
+      fee: null,
+      relayer: null,
+      replay_protection: { Sequential: {} },
     };
   }
 
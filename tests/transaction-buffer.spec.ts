@@ -1,12 +1,16 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
-import { PublicKey } from "@solana/web3.js";
+import { Keypair, PublicKey } from "@solana/web3.js";
 import { SolanaAa } from "../target/types/solana_aa";
 import { assert } from "chai";
 import * as crypto from "crypto";
 import * as borsh from "borsh";
-
-const MAX_CHUNK_SIZE = 900;
+import { confirmTransaction } from "../utils/solana";
+import {
+  MAX_CHUNK_SIZE,
+  findUnifiedStoragePDA,
+  writeTransactionBuffer,
+} from "../utils/transaction-buffer";
 
 class TestData {
   greeting: string;
@@ -30,6 +34,7 @@ describe("Transaction Buffer", () => {
 
   const program = anchor.workspace.solanaAa as Program<SolanaAa>;
   const provider = anchor.getProvider() as anchor.AnchorProvider;
+  const owner = provider.wallet.publicKey;
 
   /**
    * Generates a random data ID for storage
@@ -46,77 +51,75 @@ describe("Transaction Buffer", () => {
   }
 
   /**
-   * Splits data into chunks of specified size
+   * Serializes a TestData with `size` numbers
    */
-  function splitIntoChunks(data: Buffer, chunkSize: number): Buffer[] {
-    const chunks: Buffer[] = [];
-    for (let i = 0; i < data.length; i += chunkSize) {
-      chunks.push(data.slice(i, Math.min(i + chunkSize, data.length)));
-    }
-    return chunks;
+  function serializeTestData(greeting: string, size: number): Buffer {
+    const testData = new TestData({
+      greeting,
+      numbers: new Uint8Array(
+        Array(size)
+          .fill(0)
+          .map((_, i) => i % 256)
+      ),
+    });
+    return Buffer.from(borsh.serialize(TestData.schema, testData));
   }
 
   /**
-   * Derives the storage PDA for a given data ID
+   * Initializes a buffer for `data` declaring `dataHash`, without writing it
    */
-  function deriveStoragePda(dataId: number[]): PublicKey {
-    const [storagePda] = PublicKey.findProgramAddressSync(
-      [
-        Buffer.from("unified_storage"),
-        provider.wallet.publicKey.toBuffer(),
-        Buffer.from(dataId),
-      ],
+  async function initStorage(
+    data: Buffer,
+    dataHash: number[] = calculateHash(data),
+    writers: PublicKey[] = []
+  ) {
+    const dataId = generateDataId();
+    const signature = await program.methods
+      .initStorage(dataId, writers, data.length, dataHash)
+      .accounts({ owner, payer: owner })
+      .rpc();
+    await confirmTransaction(provider.connection, signature);
+    const [storagePda] = findUnifiedStoragePDA(
+      owner,
+      Uint8Array.from(dataId),
       program.programId
     );
-    return storagePda;
+    return { dataId, storagePda };
   }
 
   /**
-   * Stores data chunks in the program
+   * Stores chunk `index` of `data`, signed by `writer` (the owner by default)
    */
-  async function storeDataChunks(
-    dataId: number[],
-    dataHash: number[],
-    chunks: Buffer[]
-  ): Promise<void> {
-    for (let i = 0; i < chunks.length; i++) {
-      let txSignature: string;
-
-      if (i === 0) {
-        txSignature = await program.methods
-          .initStorage(
-            dataId,
-            0,
-            chunks.length,
-            dataHash,
-            Buffer.from(chunks[0])
-          )
-          .accounts({
-            payer: provider.wallet.publicKey,
-          })
-          .rpc();
-      } else {
-        txSignature = await program.methods
-          .storeChunk(
-            dataId,
-            i,
-            chunks.length,
-            dataHash,
-            Buffer.from(chunks[i])
-          )
-          .accounts({
-            payer: provider.wallet.publicKey,
-          })
-          .rpc();
-      }
-
-      console.log(
-        `Stored chunk ${i + 1}/${chunks.length}, tx: ${txSignature.substring(
-          0,
-          10
-        )}...`
-      );
-    }
+  async function storeChunk(
+    storagePda: PublicKey,
+    data: Buffer,
+    index: number,
+    writer?: Keypair
+  ) {
+    const signature = await program.methods
+      .storeChunk(
+        index,
+        data.subarray(index * MAX_CHUNK_SIZE, (index + 1) * MAX_CHUNK_SIZE)
+      )
+      .accounts({
+        unifiedStorage: storagePda,
+        writer: writer?.publicKey ?? owner,
+      })
+      .signers(writer ? [writer] : [])
+      .rpc();
+    await confirmTransaction(provider.connection, signature);
+  }
+
+  async function finalizeStorage(storagePda: PublicKey, writer?: Keypair) {
+    const signature = await program.methods
+      .finalizeStorage()
+      .accounts({
+        unifiedStorage: storagePda,
+        writer: writer?.publicKey ?? owner,
+      })
+      .signers(writer ? [writer] : [])
+      .rpc();
+    await confirmTransaction(provider.connection, signature);
   }
 
   /**
@@ -126,201 +129,207 @@ describe("Transaction Buffer", () => {
     storagePda: PublicKey,
     chunkCount: number
   ): Promise<Buffer> {
-    let reassembledData = Buffer.alloc(0);
-
-    for (let i = 0; i < chunkCount; i++) {
-      try {
-        const chunkData = await program.methods
+    const chunks = await Promise.all(
+      Array.from({ length: chunkCount }, (_, i) =>
+        program.methods
           .retrieveChunk(i)
-          .accounts({
-            unifiedStorage: storagePda,
-            payer: provider.wallet.publicKey,
-          })
-          .view();
-
-        console.log(
-          `Retrieved chunk ${i + 1}/${chunkCount}, size: ${
-            chunkData.length
-          } bytes`
-        );
-
-        reassembledData = Buffer.concat([
-          reassembledData,
-          Buffer.from(chunkData),
-        ]);
-      } catch (error) {
-        console.error(`Error retrieving chunk ${i}:`, error);
-        throw error;
-      }
-    }
-
-    return reassembledData;
+          .accounts({ unifiedStorage: storagePda })
+          .view()
+      )
+    );
+    return Buffer.concat(chunks.map((chunk) => Buffer.from(chunk)));
   }
 
   /**
    * Closes a storage account
    */
   async function closeStorageAccount(storagePda: PublicKey): Promise<void> {
-    try {
-      const txSignature = await program.methods
-        .closeStorage()
-        .accounts({
-          unifiedStorage: storagePda,
-          payer: provider.wallet.publicKey,
-        })
-        .rpc();
-
-      console.log(
-        `Closed storage account, tx: ${txSignature.substring(0, 10)}...`
-      );
-    } catch (error) {
-      console.error(`Error closing storage account:`, error);
-      throw error;
-    }
+    const txSignature = await program.methods
+      .closeStorage()
+      .accounts({ unifiedStorage: storagePda, owner })
+      .rpc();
+    await confirmTransaction(provider.connection, txSignature);
   }
 
-  it("Stores and retrieves Borsh-serialized data up to 32kb (solana single tx heap limit)", async () => {
-    // Create large test data
-    const largeGreeting =
-      "Hello, Solana! This is a large test of oversized data handling with multiple chunks across multiple accounts. We'll use many different PDAs to store different parts of the data. This demonstrates our ability to handle data much larger than a single Solana account can store.";
-
-    const largeNumbers = new Uint8Array(
-      Array(24000)
-        .fill(0)
-        .map((_, i) => i % 256)
-    );
-
-    const largeTestData = new TestData({
-      greeting: largeGreeting,
-      numbers: largeNumbers,
-    });
-    const serializedData = borsh.serialize(
-      TestData.schema,
-      largeTestData
-    ) as Buffer;
-
-    console.log("Total serialized data size:", serializedData.length, "bytes");
-
-    // Generate data ID and calculate hash
-    const dataId = generateDataId();
-    const dataHash = calculateHash(serializedData);
-
-    // Split data into chunks and store them
-    const chunks = splitIntoChunks(serializedData, MAX_CHUNK_SIZE);
-    console.log(
-      `Split data into ${chunks.length} chunks of max ${MAX_CHUNK_SIZE} bytes each`
-    );
+  it("Stores and retrieves Borsh-serialized data beyond the 32 KB heap", async () => {
+    const greeting = "Hello, Solana! This payload no longer fits on the heap.";
+    const serializedData = serializeTestData(greeting, 100_000);
+    const chunkCount = Math.ceil(serializedData.length / MAX_CHUNK_SIZE);
 
-    await storeDataChunks(dataId, dataHash, chunks);
+    const storagePda = await writeTransactionBuffer(program, serializedData);
 
-    // Get storage PDA and verify metadata
-    const storagePda = deriveStoragePda(dataId);
     const metadata = await program.methods
       .getDataMetadata()
-      .accounts({
-        unifiedStorage: storagePda,
-        payer: provider.wallet.publicKey,
-      })
+      .accounts({ unifiedStorage: storagePda })
       .view();
+    assert.isTrue(metadata.owner.equals(owner));
+    assert.isEmpty(metadata.writers);
+    assert.equal(metadata.dataLen, serializedData.length);
+    assert.equal(metadata.totalChunks, chunkCount);
+    assert.equal(metadata.chunksStored, chunkCount);
+    assert.isTrue(metadata.finalized);
 
-    assert.equal(
-      metadata.chunksStored,
-      chunks.length,
-      `Failed to store all chunks`
-    );
-
-    console.log(`Using ${chunks.length} storage accounts`);
-
-    // Retrieve and reassemble data
     const reassembledData = await retrieveAndReassembleData(
       storagePda,
-      chunks.length
+      chunkCount
     );
-
-    // Verify data integrity
-    const reassembledHash = calculateHash(reassembledData);
-    const hashesMatch =
-      JSON.stringify(reassembledHash) === JSON.stringify(dataHash);
-
-    console.log(
-      "Full data integrity verification (hash comparison):",
-      hashesMatch
+    assert.deepEqual(
+      calculateHash(reassembledData),
+      calculateHash(serializedData)
     );
-    assert.isTrue(hashesMatch, "Data hash verification failed");
 
-    // Deserialize and verify data
     const deserializedData = borsh.deserialize(
       TestData.schema,
       reassembledData
     ) as TestData;
+    assert.equal(deserializedData.greeting, greeting);
+    assert.equal(deserializedData.numbers.length, 100_000);
 
-    console.log("Deserialized data:", {
-      greeting: deserializedData.greeting,
-      numbersPreview:
-        Array.from(deserializedData.numbers.slice(0, 10)).join(",") + "...",
-    });
+    await closeStorageAccount(storagePda);
+  });
 
-    assert.equal(deserializedData.greeting, largeGreeting);
-    assert.equal(deserializedData.numbers.length, largeNumbers.length);
-    assert.deepEqual(
-      Array.from(deserializedData.numbers),
-      Array.from(largeNumbers)
-    );
+  it("Tests storage with small data in single chunk", async () => {
+    const serializedData = serializeTestData("Small data test", 50);
 
-    console.log(`Total chunks: ${chunks.length}`);
-    console.log(`Original data size: ${serializedData.length} bytes`);
-    console.log(`Reassembled data size: ${reassembledData.length} bytes`);
+    const storagePda = await writeTransactionBuffer(program, serializedData);
+    const reassembledData = await retrieveAndReassembleData(storagePda, 1);
+    assert.isTrue(reassembledData.equals(serializedData));
 
-    // Clean up
     await closeStorageAccount(storagePda);
   });
 
-  it("Tests storage with small data in single account", async () => {
-    // Create small test data
-    const smallTestData = new TestData({
-      greeting: "Small data test",
-      numbers: new Uint8Array(
-        Array(50)
-          .fill(0)
-          .map((_, i) => i % 256)
-      ),
-    });
+  it("rejects storing a chunk twice", async () => {
+    const serializedData = serializeTestData("Duplicate chunk", 50);
+    const { storagePda } = await initStorage(serializedData);
+    await storeChunk(storagePda, serializedData, 0);
 
-    const serializedData = borsh.serialize(
-      TestData.schema,
-      smallTestData
-    ) as Buffer;
-    const dataId = generateDataId();
-    const dataHash = calculateHash(serializedData);
+    try {
+      await storeChunk(storagePda, serializedData, 0);
+      assert.fail("Expected a duplicate chunk to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "ChunkAlreadyStored");
+    } finally {
+      await closeStorageAccount(storagePda);
+    }
+  });
 
-    console.log(
-      `Storing small data (${serializedData.length} bytes) in a single account`
-    );
+  it("rejects writes to a finalized buffer", async () => {
+    const serializedData = serializeTestData("Finalized buffer", 50);
+    const storagePda = await writeTransactionBuffer(program, serializedData);
 
-    // Split and store data
-    const chunks = splitIntoChunks(serializedData, MAX_CHUNK_SIZE);
-    await storeDataChunks(dataId, dataHash, chunks);
+    try {
+      await storeChunk(storagePda, serializedData, 0);
+      assert.fail("Expected a write to a finalized buffer to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "StorageFinalized");
+    } finally {
+      await closeStorageAccount(storagePda);
+    }
+  });
 
-    // Retrieve and verify data
-    const storagePda = deriveStoragePda(dataId);
-    const reassembledData = await retrieveAndReassembleData(
-      storagePda,
-      chunks.length
+  it("rejects a chunk whose range is not allocated yet", async () => {
+    // 20 KB needs one `extend_storage` beyond the initial 10 KB.
+    const serializedData = serializeTestData("Unallocated chunk", 20_000);
+    const lastIndex = Math.ceil(serializedData.length / MAX_CHUNK_SIZE) - 1;
+    const { storagePda } = await initStorage(serializedData);
+
+    try {
+      await storeChunk(storagePda, serializedData, lastIndex);
+      assert.fail("Expected an unallocated chunk to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "ChunkNotAllocated");
+    } finally {
+      await closeStorageAccount(storagePda);
+    }
+  });
+
+  it("rejects finalizing a buffer with missing chunks", async () => {
+    const serializedData = serializeTestData("Missing chunks", 2_000);
+    const { storagePda } = await initStorage(serializedData);
+    // Chunk 0 of 3 is stored; 1 and 2 never are.
+    await storeChunk(storagePda, serializedData, 0);
+
+    try {
+      await finalizeStorage(storagePda);
+      assert.fail("Expected an incomplete buffer to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "StorageIncomplete");
+    } finally {
+      await closeStorageAccount(storagePda);
+    }
+  });
+
+  it("rejects finalizing a payload that does not match its hash", async () => {
+    const serializedData = serializeTestData("Wrong hash", 50);
+    const { storagePda } = await initStorage(
+      serializedData,
+      calculateHash(Buffer.from("something else"))
     );
+    await storeChunk(storagePda, serializedData, 0);
+
+    try {
+      await finalizeStorage(storagePda);
+      assert.fail("Expected a hash mismatch to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "InvalidDataHash");
+    } finally {
+      await closeStorageAccount(storagePda);
+    }
+  });
 
-    // Verify data integrity
-    const reassembledHash = calculateHash(reassembledData);
-    const dataIntegrityMatch =
-      JSON.stringify(reassembledHash) === JSON.stringify(dataHash);
+  it("lets a named writer finish an upload the owner started", async () => {
+    const relayer = Keypair.generate();
+    const serializedData = serializeTestData("Relayed upload", 1_000);
+    const { storagePda } = await initStorage(
+      serializedData,
+      calculateHash(serializedData),
+      [relayer.publicKey]
+    );
+    await storeChunk(storagePda, serializedData, 0);
+    await storeChunk(storagePda, serializedData, 1, relayer);
+    await finalizeStorage(storagePda, relayer);
 
-    console.log("Small data integrity verification:", dataIntegrityMatch);
-    assert.isTrue(dataIntegrityMatch, "Small data integrity failed");
+    const metadata = await program.methods
+      .getDataMetadata()
+      .accounts({ unifiedStorage: storagePda })
+      .view();
+    assert.isTrue(metadata.owner.equals(owner));
+    assert.isTrue(metadata.writers[0].equals(relayer.publicKey));
+    assert.isTrue(metadata.finalized);
 
-    // Clean up
     await closeStorageAccount(storagePda);
+  });
 
-    console.log(
-      "Test completed - successfully stored and retrieved data using multiple accounts"
-    );
+  it("rejects a chunk from a signer that is not a writer", async () => {
+    const stranger = Keypair.generate();
+    const serializedData = serializeTestData("Unauthorized writer", 50);
+    const { storagePda } = await initStorage(serializedData);
+
+    try {
+      await storeChunk(storagePda, serializedData, 0, stranger);
+      assert.fail("Expected a chunk from a non-writer to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "NotStorageWriter");
+    } finally {
+      await closeStorageAccount(storagePda);
+    }
+  });
+
+  it("rejects reclaiming a buffer before it expires", async () => {
+    const serializedData = serializeTestData("Not expired", 50);
+    const { storagePda } = await initStorage(serializedData);
+
+    try {
+      // Anyone may send this; only the timeout stops it.
+      await program.methods
+        .reclaimExpiredStorage()
+        .accounts({ unifiedStorage: storagePda, owner })
+        .rpc();
+      assert.fail("Expected a fresh buffer not to be reclaimable");
+    } catch (error: any) {
+      assert.include(error.toString(), "StorageNotExpired");
+    } finally {
+      await closeStorageAccount(storagePda);
+    }
   });
 });
@@ -6,16 +6,47 @@ import { createHash } from "crypto";
 import { toBytes } from "viem";
 import { borshUtils, Transaction } from "../borsh";
 import { SolanaAa } from "../target/types/solana_aa";
+import { privateKeyToAccount } from "viem/accounts";
 import { buildEthereumIdentity, buildOidcIdentity } from "../utils/identity";
 import { cleanUpProgramState, findAbstractAccountPDA } from "../utils/program";
 import { confirmTransaction } from "../utils/solana";
-import { groth16ProofFromFixture, loadZkOidcFixture } from "../utils/zk-oidc";
+import { executeEk256Action } from "../utils/test-helpers";
+import { writeTransactionBuffer } from "../utils/transaction-buffer";
+import {
+  findOidcKeyRegistryPDA,
+  findZkVkeyRegistryPDA,
+  groth16ProofFromFixture,
+  loadZkOidcFixture,
+  oidcIssuerHash,
+  oidcSubjectCommitment,
+  vkeyHashBytes,
+  ZkOidcAggregateFixture,
+} from "../utils/zk-oidc";
 
 const ETH_ADDRESS = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+// Private key of ETH_ADDRESS (Hardhat account #0).
+const HARDHAT_KEY =
+  "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80" as const;
 
 // On-chain Groth16 verification needs more than the default 200k CU budget.
 const ZK_VERIFY_COMPUTE_UNITS = 500_000;
 
+// Tolerance applied to the JWT iat/exp check against the validator clock.
+const OIDC_CLOCK_SKEW = 60;
+// Mirrors OidcKeyRegistry::MAX_CLOCK_SKEW.
+const MAX_OIDC_CLOCK_SKEW = 5 * 60;
+// No timelock on key additions, so the fixture key is usable right away.
+const OIDC_KEY_ADD_DELAY = new BN(0);
+// Likewise for the fixture guest's vkey.
+const ZK_VKEY_ADD_DELAY = new BN(0);
+// Vkeys registered next to the fixture guest's, which no proof is made for.
+const UNPROVEN_VKEY_HASH = Array.from(Buffer.alloc(32, 0xcd));
+const DEPRECATED_VKEY_HASH = Array.from(Buffer.alloc(32, 0xef));
+
+// u64::MAX: ZkVkeyEntry.deprecation_slot / OidcKeyEntry.not_after sentinel
+// for "never deprecated" / "no scheduled expiry".
+const U64_MAX = new BN("18446744073709551615");
+
 describe("Execute ZK OIDC", () => {
   const provider = anchor.getProvider() as anchor.AnchorProvider;
   const program = anchor.workspace.solanaAa as anchor.Program<SolanaAa>;
@@ -24,9 +55,42 @@ describe("Execute ZK OIDC", () => {
 
   const fixture = loadZkOidcFixture("zk-oidc-add-identity.json");
   const groth16Proof = groth16ProofFromFixture(fixture);
+  // Two subjects of the fixture issuer co-approving one transaction, generated
+  // with `fixture --aggregate --account-nonce 2`.
+  const aggregateFixture = loadZkOidcFixture<ZkOidcAggregateFixture>(
+    "zk-oidc-aggregate-add-identity.json"
+  );
+  const [oidcKeyRegistry] = findOidcKeyRegistryPDA(
+    fixture.iss,
+    program.programId
+  );
+  // The fixture JWT is RS256, signed by the test RSA key, and carries the
+  // transaction nonce verbatim in the standard `nonce` claim.
   const fixtureKeyEntry = {
-    iss: fixture.iss,
     pkHash: Array.from(Buffer.from(fixture.pkHash, "hex")),
+    keyType: { rsa: {} },
+    kid: "test-key-1",
+    notBefore: new BN(0),
+    notAfter: U64_MAX,
+  };
+
+  const removeFixtureKey = async () => {
+    const signature = await program.methods
+      .removeOidcKey(fixtureKeyEntry.pkHash)
+      .accountsPartial({ oidcKeyRegistry })
+      .rpc();
+    await confirmTransaction(connection, signature);
+  };
+
+  // Re-registers the fixture key with some fields overridden.
+  const replaceFixtureKey = async (overrides: Record<string, unknown>) => {
+    await removeFixtureKey();
+
+    const addSignature = await program.methods
+      .addOidcKey({ ...fixtureKeyEntry, ...overrides })
+      .accountsPartial({ oidcKeyRegistry })
+      .rpc();
+    await confirmTransaction(connection, addSignature);
   };
 
   // The Borsh transaction the fixture proof is bound to (account 0, nonce 0,
@@ -46,6 +110,9 @@ describe("Execute ZK OIDC", () => {
         },
       },
     },
+    fee: null,
+    relayer: null,
+    replay_protection: { Sequential: {} },
   };
 
   // Same transaction in the Anchor instruction-argument format.
@@ -70,36 +137,84 @@ describe("Execute ZK OIDC", () => {
         },
       },
     },
+    fee: null,
+    relayer: null,
+    replayProtection: { sequential: {} },
   };
 
   const oidcIdentity = buildOidcIdentity(
     {
       iss: fixture.iss,
       aud: fixture.aud,
-      emailHash: Buffer.from(fixture.emailHash, "hex"),
+      subjectCommitment: Buffer.from(fixture.subjectCommitment, "hex"),
     },
     null
   );
 
-  const resetOidcRegistry = async () => {
+  const resetOidcRegistry = async (
+    nonceBinding: Record<string, unknown> = { raw: {} }
+  ) => {
+    // Only an empty registry can be closed.
+    try {
+      await removeFixtureKey();
+    } catch {
+      // Registry or key did not exist.
+    }
     try {
       await program.methods
         .closeOidcRegistry()
-        .accounts({ authority: provider.wallet.publicKey })
+        .accountsPartial({
+          authority: provider.wallet.publicKey,
+          oidcKeyRegistry,
+        })
         .rpc();
     } catch {
       // Registry did not exist yet.
     }
 
-    const initSignature = await program.methods.initOidcRegistry().rpc();
+    const initSignature = await program.methods
+      .initOidcRegistry(
+        Array.from(oidcIssuerHash(fixture.iss)),
+        fixture.iss,
+        new BN(OIDC_CLOCK_SKEW),
+        nonceBinding as any,
+        OIDC_KEY_ADD_DELAY
+      )
+      .rpc();
     await confirmTransaction(connection, initSignature);
 
     const addKeySignature = await program.methods
       .addOidcKey(fixtureKeyEntry)
+      .accountsPartial({ oidcKeyRegistry })
       .rpc();
     await confirmTransaction(connection, addKeySignature);
   };
 
+  // The vkey registry is never closed, so it is created once, with both fixture
+  // guests' vkeys, and shared by every test.
+  const [zkVkeyRegistry] = findZkVkeyRegistryPDA(program.programId);
+  const initZkVkeyRegistry = async () => {
+    if (await connection.getAccountInfo(zkVkeyRegistry)) {
+      return;
+    }
+
+    const initSignature = await program.methods
+      .initZkVkeyRegistry(ZK_VKEY_ADD_DELAY)
+      .rpc();
+    await confirmTransaction(connection, initSignature);
+
+    for (const { vkeyHash } of [fixture, aggregateFixture]) {
+      const addSignature = await program.methods
+        .addZkVkey({
+          vkeyHash: vkeyHashBytes(vkeyHash),
+          activationSlot: new BN(0),
+          deprecationSlot: U64_MAX,
+        })
+        .rpc();
+      await confirmTransaction(connection, addSignature);
+    }
+  };
+
   const createOidcAccount = async () => {
     const signature = await program.methods.createAccount(oidcIdentity).rpc();
     await confirmTransaction(connection, signature);
@@ -110,6 +225,7 @@ describe("Execute ZK OIDC", () => {
   ) => {
     const signature = await program.methods
       .executeZkOidc(transactionArg.accountId, transactionArg, groth16Proof)
+      .accountsPartial({ oidcKeyRegistry })
       .preInstructions([
         ComputeBudgetProgram.setComputeUnitLimit({
           units: ZK_VERIFY_COMPUTE_UNITS,
@@ -121,6 +237,10 @@ describe("Execute ZK OIDC", () => {
     return signature;
   };
 
+  before(async () => {
+    await initZkVkeyRegistry();
+  });
+
   beforeEach(async () => {
     await cleanUpProgramState(program, connection, provider);
     await resetOidcRegistry();
@@ -137,6 +257,37 @@ describe("Execute ZK OIDC", () => {
     assert.strictEqual(nonce, fixture.nonce);
   });
 
+  it("commits the subject blinded by the user salt", () => {
+    const commitment = oidcSubjectCommitment(
+      Buffer.from(fixture.salt, "hex"),
+      fixture.sub
+    );
+    assert.strictEqual(commitment.toString("hex"), fixture.subjectCommitment);
+
+    const unsalted = createHash("sha256").update(fixture.sub).digest("hex");
+    assert.notStrictEqual(unsalted, fixture.subjectCommitment);
+  });
+
+  it("binds a token lifetime that is live on the validator clock", async () => {
+    const slot = await connection.getSlot();
+    const now = await connection.getBlockTime(slot);
+
+    assert.isAtMost(fixture.iat, now! + OIDC_CLOCK_SKEW);
+    assert.isAtLeast(fixture.exp + OIDC_CLOCK_SKEW, now!);
+  });
+
+  it("rejects a registry clock skew above the maximum", async () => {
+    try {
+      await program.methods
+        .setOidcClockSkew(new BN(MAX_OIDC_CLOCK_SKEW + 1))
+        .accountsPartial({ oidcKeyRegistry })
+        .rpc();
+      assert.fail("Expected oversized clock skew to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "ClockSkewTooLarge");
+    }
+  });
+
   it("executes an AddIdentity transaction authorized by a ZK OIDC proof", async () => {
     await createOidcAccount();
 
@@ -156,6 +307,41 @@ describe("Execute ZK OIDC", () => {
     );
   });
 
+  it("executes a transaction and proof read from a buffer", async () => {
+    await createOidcAccount();
+    const unifiedStorage = await writeTransactionBuffer(
+      program,
+      borshUtils.serialize.executeZkOidcArgs({
+        transaction: fixtureTransaction,
+        groth16_proof: {
+          proof: groth16Proof.proof,
+          public_values: groth16Proof.publicValues,
+          vkey_hash: Uint8Array.from(groth16Proof.vkeyHash),
+        },
+      })
+    );
+
+    const signature = await program.methods
+      .executeZkOidcFromBuffer(fixtureTransactionArg.accountId)
+      .accountsPartial({
+        oidcKeyRegistry,
+        unifiedStorage,
+        owner: provider.wallet.publicKey,
+      })
+      .preInstructions([
+        ComputeBudgetProgram.setComputeUnitLimit({
+          units: ZK_VERIFY_COMPUTE_UNITS,
+        }),
+      ])
+      .rpc();
+    await confirmTransaction(connection, signature);
+
+    const [accountPDA] = findAbstractAccountPDA(new BN(0), program.programId);
+    const accountInfo = await program.account.abstractAccount.fetch(accountPDA);
+    assert.strictEqual(accountInfo.nonce.toString(), "1");
+    assert.isNull(await connection.getAccountInfo(unifiedStorage));
+  });
+
   it("rejects replaying the same proof after the account nonce moved", async () => {
     await createOidcAccount();
     await executeZkOidc(fixtureTransactionArg);
@@ -203,11 +389,7 @@ describe("Execute ZK OIDC", () => {
 
   it("rejects proofs signed by a key that is not in the registry", async () => {
     await createOidcAccount();
-
-    const removeSignature = await program.methods
-      .removeOidcKey(fixtureKeyEntry)
-      .rpc();
-    await confirmTransaction(connection, removeSignature);
+    await removeFixtureKey();
 
     try {
       await executeZkOidc(fixtureTransactionArg);
@@ -217,6 +399,265 @@ describe("Execute ZK OIDC", () => {
     }
   });
 
+  it("rejects proofs signed by a key that is not yet valid", async () => {
+    await createOidcAccount();
+    // Scheduled rotation: the key is registered but only accepted from a
+    // future timestamp.
+    await replaceFixtureKey({ notBefore: U64_MAX.subn(1) });
+
+    try {
+      await executeZkOidc(fixtureTransactionArg);
+      assert.fail("Expected a not-yet-valid key to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "OidcKeyNotActive");
+    }
+  });
+
+  it("rejects registering a key whose validity window already ended", async () => {
+    await removeFixtureKey();
+
+    // not_before is raised to at least the current time, so a window in the
+    // past is empty.
+    try {
+      await program.methods
+        .addOidcKey({ ...fixtureKeyEntry, notAfter: new BN(1) })
+        .accountsPartial({ oidcKeyRegistry })
+        .rpc();
+      assert.fail("Expected an expired key window to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "InvalidOidcKeyWindow");
+    }
+  });
+
+  it("rejects a registry passed for a different issuer", async () => {
+    await createOidcAccount();
+
+    const otherIss = "https://issuer.example.com";
+    const [otherRegistry] = findOidcKeyRegistryPDA(
+      otherIss,
+      program.programId
+    );
+    try {
+      await program.methods
+        .removeOidcKey(fixtureKeyEntry.pkHash)
+        .accountsPartial({ oidcKeyRegistry: otherRegistry })
+        .rpc();
+    } catch {
+      // Registry or key did not exist.
+    }
+    try {
+      await program.methods
+        .closeOidcRegistry()
+        .accountsPartial({
+          authority: provider.wallet.publicKey,
+          oidcKeyRegistry: otherRegistry,
+        })
+        .rpc();
+    } catch {
+      // Registry did not exist yet.
+    }
+    const initSignature = await program.methods
+      .initOidcRegistry(
+        Array.from(oidcIssuerHash(otherIss)),
+        otherIss,
+        new BN(OIDC_CLOCK_SKEW),
+        { raw: {} },
+        OIDC_KEY_ADD_DELAY
+      )
+      .rpc();
+    await confirmTransaction(connection, initSignature);
+    const addSignature = await program.methods
+      .addOidcKey(fixtureKeyEntry)
+      .accountsPartial({ oidcKeyRegistry: otherRegistry })
+      .rpc();
+    await confirmTransaction(connection, addSignature);
+
+    try {
+      await program.methods
+        .executeZkOidc(
+          fixtureTransactionArg.accountId,
+          fixtureTransactionArg,
+          groth16Proof
+        )
+        .accountsPartial({ oidcKeyRegistry: otherRegistry })
+        .preInstructions([
+          ComputeBudgetProgram.setComputeUnitLimit({
+            units: ZK_VERIFY_COMPUTE_UNITS,
+          }),
+        ])
+        .rpc();
+      assert.fail("Expected a foreign issuer's registry to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "OidcIssuerMismatch");
+    }
+  });
+
+  it("rejects a registry whose issuer hash does not match the issuer", async () => {
+    const wrongHash = Array.from(oidcIssuerHash("https://issuer.example.com"));
+
+    try {
+      await program.methods
+        .initOidcRegistry(
+          wrongHash,
+          fixture.iss,
+          new BN(OIDC_CLOCK_SKEW),
+          { raw: {} },
+          OIDC_KEY_ADD_DELAY
+        )
+        .rpc();
+      assert.fail("Expected a mismatched issuer hash to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "IssuerHashMismatch");
+    }
+  });
+
+  it("shrinks the registry when a key is removed", async () => {
+    const before = await connection.getAccountInfo(oidcKeyRegistry);
+    await removeFixtureKey();
+    const after = await connection.getAccountInfo(oidcKeyRegistry);
+
+    // pk_hash + key_type + kid + not_before + not_after
+    const entrySize = 32 + 1 + 4 + fixtureKeyEntry.kid.length + 8 + 8;
+    assert.strictEqual(before!.data.length - after!.data.length, entrySize);
+    assert.isBelow(after!.lamports, before!.lamports);
+  });
+
+  it("rejects backdated and repeated vkey deprecations", async () => {
+    const addSignature = await program.methods
+      .addZkVkey({
+        vkeyHash: DEPRECATED_VKEY_HASH,
+        activationSlot: new BN(0),
+        deprecationSlot: U64_MAX,
+      })
+      .rpc();
+    await confirmTransaction(connection, addSignature);
+
+    try {
+      await program.methods
+        .deprecateZkVkey(DEPRECATED_VKEY_HASH, new BN(0))
+        .rpc();
+      assert.fail("Expected a backdated deprecation to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "ZkVkeyDeprecationInPast");
+    }
+
+    const deprecationSlot = new BN((await connection.getSlot()) + 1_000);
+    const deprecateSignature = await program.methods
+      .deprecateZkVkey(DEPRECATED_VKEY_HASH, deprecationSlot)
+      .rpc();
+    await confirmTransaction(connection, deprecateSignature);
+
+    try {
+      await program.methods
+        .deprecateZkVkey(DEPRECATED_VKEY_HASH, U64_MAX)
+        .rpc();
+      assert.fail("Expected a deprecated vkey to stay deprecated");
+    } catch (error: any) {
+      assert.include(error.toString(), "ZkVkeyAlreadyDeprecated");
+    }
+  });
+
+  it("delays a new vkey's activation by the registry's add delay", async () => {
+    // The delay only ever grows, so this leaves it raised for later tests;
+    // vkeys already registered, like the fixture guest's, are unaffected.
+    const vkeyAddDelay = 1_000;
+    const delaySignature = await program.methods
+      .setZkVkeyAddDelay(new BN(vkeyAddDelay))
+      .rpc();
+    await confirmTransaction(connection, delaySignature);
+
+    const slot = await connection.getSlot();
+    const addSignature = await program.methods
+      .addZkVkey({
+        vkeyHash: UNPROVEN_VKEY_HASH,
+        activationSlot: new BN(0),
+        deprecationSlot: U64_MAX,
+      })
+      .rpc();
+    await confirmTransaction(connection, addSignature);
+
+    const registry = await program.account.zkVkeyRegistry.fetch(
+      zkVkeyRegistry
+    );
+    const entry = registry.vkeys.find((vkey) =>
+      Buffer.from(vkey.vkeyHash).equals(Buffer.from(UNPROVEN_VKEY_HASH))
+    );
+    assert.isAtLeast(entry!.activationSlot.toNumber(), slot + vkeyAddDelay);
+
+    try {
+      await program.methods.setZkVkeyAddDelay(new BN(0)).rpc();
+      assert.fail("Expected the add delay not to decrease");
+    } catch (error: any) {
+      assert.include(error.toString(), "ZkVkeyAddDelayDecrease");
+    }
+  });
+
+  it("rejects proofs claiming a vkey that is not in the registry", async () => {
+    await createOidcAccount();
+
+    const unknownVkeyProof = {
+      ...groth16Proof,
+      vkeyHash: Array.from(Buffer.alloc(32, 0xab)),
+    };
+
+    try {
+      await program.methods
+        .executeZkOidc(
+          fixtureTransactionArg.accountId,
+          fixtureTransactionArg,
+          unknownVkeyProof
+        )
+        .accountsPartial({ oidcKeyRegistry })
+        .preInstructions([
+          ComputeBudgetProgram.setComputeUnitLimit({
+            units: ZK_VERIFY_COMPUTE_UNITS,
+          }),
+        ])
+        .rpc();
+      assert.fail("executeZkOidc resolved but a rejection was expected");
+    } catch (error: any) {
+      assert.include(error.toString(), "ZkVkeyNotAccepted");
+    }
+  });
+
+  it("rejects proofs whose JWT alg does not match the registered key type", async () => {
+    await createOidcAccount();
+    await replaceFixtureKey({ keyType: { p256: {} } });
+
+    try {
+      await executeZkOidc(fixtureTransactionArg);
+      assert.fail("Expected key type mismatch to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "OidcKeyTypeMismatch");
+    }
+  });
+
+  it("applies the issuer's sha256 nonce binding to the transaction nonce", async () => {
+    await createOidcAccount();
+    // The fixture token carries the raw nonce, so an issuer configured to
+    // hash it must reject the proof.
+    await resetOidcRegistry({ sha256: {} });
+
+    try {
+      await executeZkOidc(fixtureTransactionArg);
+      assert.fail("Expected sha256 nonce binding to reject a raw nonce");
+    } catch (error: any) {
+      assert.include(error.toString(), "TransactionBindingMismatch");
+    }
+  });
+
+  it("rejects a nonce read from a claim the issuer is not configured for", async () => {
+    await createOidcAccount();
+    await resetOidcRegistry({ customClaim: { "0": "tx_nonce" } });
+
+    try {
+      await executeZkOidc(fixtureTransactionArg);
+      assert.fail("Expected custom nonce claim mismatch");
+    } catch (error: any) {
+      assert.include(error.toString(), "NonceClaimMismatch");
+    }
+  });
+
   it("rejects a valid proof for an identity the account does not have", async () => {
     const signature = await program.methods
       .createAccount(buildEthereumIdentity(ETH_ADDRESS, null))
@@ -237,6 +678,7 @@ describe("Execute ZK OIDC", () => {
     const tampered = {
       proof: Buffer.from(groth16Proof.proof),
       publicValues: Buffer.from(groth16Proof.publicValues),
+      vkeyHash: groth16Proof.vkeyHash,
     };
     // Flip a byte in the proof body; it stays well-formed but no longer
     // satisfies the pairing check, so on-chain verification must reject it.
@@ -249,6 +691,7 @@ describe("Execute ZK OIDC", () => {
           fixtureTransactionArg,
           tampered
         )
+        .accountsPartial({ oidcKeyRegistry })
         .preInstructions([
           ComputeBudgetProgram.setComputeUnitLimit({
             units: ZK_VERIFY_COMPUTE_UNITS,
@@ -267,6 +710,7 @@ describe("Execute ZK OIDC", () => {
     const tampered = {
       proof: Buffer.from(groth16Proof.proof),
       publicValues: Buffer.from(groth16Proof.publicValues),
+      vkeyHash: groth16Proof.vkeyHash,
     };
     // The proof commits to sha256(publicValues); changing any byte breaks that
     // binding and the Groth16 verification fails.
@@ -279,6 +723,7 @@ describe("Execute ZK OIDC", () => {
           fixtureTransactionArg,
           tampered
         )
+        .accountsPartial({ oidcKeyRegistry })
         .preInstructions([
           ComputeBudgetProgram.setComputeUnitLimit({
             units: ZK_VERIFY_COMPUTE_UNITS,
@@ -290,4 +735,172 @@ describe("Execute ZK OIDC", () => {
       assert.include(error.toString(), "ProofVerificationFailed");
     }
   });
+
+  it("executes a transaction co-approved by two identities in one aggregated proof", async () => {
+    // An Ethereum owner adds both OIDC identities, moving the account nonce to
+    // the 2 the aggregated proof is bound to.
+    const createSignature = await program.methods
+      .createAccount(
+        buildEthereumIdentity(privateKeyToAccount(HARDHAT_KEY).address, null)
+      )
+      .rpc();
+    await confirmTransaction(connection, createSignature);
+    for (const jwt of aggregateFixture.jwts) {
+      await executeEk256Action(program, {
+        accountId: BigInt(aggregateFixture.accountId),
+        ethPrivateKey: HARDHAT_KEY,
+        action: {
+          AddIdentity: {
+            identity: {
+              Oidc: {
+                iss: jwt.iss,
+                aud: jwt.aud,
+                subject_commitment: Buffer.from(jwt.subjectCommitment, "hex"),
+              },
+            },
+            permissions: null,
+          },
+        },
+      });
+    }
+
+    const aggregateTransaction: Transaction = {
+      ...fixtureTransaction,
+      account_id: BigInt(aggregateFixture.accountId),
+      nonce: BigInt(aggregateFixture.accountNonce),
+      action: {
+        AddIdentity: {
+          identity: {
+            Wallet: {
+              Ethereum: toBytes(aggregateFixture.ethAddress as `0x${string}`),
+            },
+          },
+          permissions: {
+            enable_act_as: true,
+          },
+        },
+      },
+    };
+    assert.strictEqual(
+      Buffer.from(
+        borshUtils.serialize.transaction(aggregateTransaction)
+      ).toString("hex"),
+      aggregateFixture.transactionBorsh
+    );
+
+    const aggregateTransactionArg = {
+      ...fixtureTransactionArg,
+      accountId: new BN(aggregateFixture.accountId),
+      nonce: new BN(aggregateFixture.accountNonce),
+      action: {
+        addIdentity: {
+          "0": {
+            identity: {
+              wallet: {
+                "0": {
+                  ethereum: {
+                    "0": Array.from(
+                      toBytes(aggregateFixture.ethAddress as `0x${string}`)
+                    ),
+                  },
+                },
+              },
+            },
+            permissions: {
+              enableActAs: true,
+            },
+          },
+        },
+      },
+    };
+
+    // One registry per JWT, in proof order.
+    const signature = await program.methods
+      .executeZkOidcAggregated(
+        aggregateTransactionArg.accountId,
+        aggregateTransactionArg,
+        groth16ProofFromFixture(aggregateFixture)
+      )
+      .remainingAccounts(
+        aggregateFixture.jwts.map((jwt) => ({
+          pubkey: findOidcKeyRegistryPDA(jwt.iss, program.programId)[0],
+          isSigner: false,
+          isWritable: false,
+        }))
+      )
+      .preInstructions([
+        ComputeBudgetProgram.setComputeUnitLimit({
+          units: ZK_VERIFY_COMPUTE_UNITS,
+        }),
+      ])
+      .rpc();
+    await confirmTransaction(connection, signature);
+
+    const [accountPDA] = findAbstractAccountPDA(
+      aggregateTransactionArg.accountId,
+      program.programId
+    );
+    const accountInfo = await program.account.abstractAccount.fetch(accountPDA);
+    assert.strictEqual(accountInfo.nonce.toString(), "3");
+    assert.strictEqual(accountInfo.identities.length, 4);
+
+    const addedIdentityBytes =
+      accountInfo.identities[3].identity?.wallet?.[0].ethereum?.[0];
+    assert.deepEqual(
+      Array.from(addedIdentityBytes || []),
+      Array.from(toBytes(aggregateFixture.ethAddress as `0x${string}`))
+    );
+  });
+
+  it("rejects a single-JWT proof submitted as an aggregated proof", async () => {
+    await createOidcAccount();
+
+    // The proof itself verifies; its public values carry the single-JWT
+    // version byte, which the aggregated decoder refuses.
+    try {
+      await program.methods
+        .executeZkOidcAggregated(
+          fixtureTransactionArg.accountId,
+          fixtureTransactionArg,
+          groth16Proof
+        )
+        .remainingAccounts([
+          { pubkey: oidcKeyRegistry, isSigner: false, isWritable: false },
+        ])
+        .preInstructions([
+          ComputeBudgetProgram.setComputeUnitLimit({
+            units: ZK_VERIFY_COMPUTE_UNITS,
+          }),
+        ])
+        .rpc();
+      assert.fail(
+        "executeZkOidcAggregated resolved but a rejection was expected"
+      );
+    } catch (error: any) {
+      assert.include(error.toString(), "InvalidPublicValues");
+    }
+  });
+
+  // Runs last: it deprecates the fixture guest's vkey in the shared registry
+  // for good.
+  it("rejects proofs from a guest whose vkey was deprecated", async () => {
+    await createOidcAccount();
+
+    // Deprecation cannot be backdated, so schedule it just ahead and wait.
+    const deprecationSlot = (await connection.getSlot()) + 5;
+    const deprecateSignature = await program.methods
+      .deprecateZkVkey(vkeyHashBytes(fixture.vkeyHash), new BN(deprecationSlot))
+      .rpc();
+    await confirmTransaction(connection, deprecateSignature);
+    while ((await connection.getSlot()) < deprecationSlot) {
+      await new Promise((resolve) => setTimeout(resolve, 200));
+    }
+
+    try {
+      await executeZkOidc(fixtureTransactionArg);
+      assert.fail("Expected deprecated vkey to be rejected");
+    } catch (error: any) {
+      assert.include(error.toString(), "ZkVkeyNotAccepted");
+    }
+  });
 });
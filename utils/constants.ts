@@ -17,6 +17,10 @@ export const SOLANA_PRE_COMPILED_ERRORS = {
 export const ABSTRACT_ACCOUNT_SEED = Buffer.from("abstract_account");
 export const ACCOUNT_MANAGER_SEED = Buffer.from("account_manager");
 export const OIDC_KEY_REGISTRY_SEED = Buffer.from("oidc_key_registry");
+export const ZK_VKEY_REGISTRY_SEED = Buffer.from("zk_vkey_registry");
+export const UNIFIED_STORAGE_SEED = Buffer.from("unified_storage");
+export const PAYMASTER_SEED = Buffer.from("paymaster");
+export const NONCE_BITMAP_SEED = Buffer.from("nonce_bitmap");
 
 // Sig Network chain-signatures program (canonical devnet/mainnet). Stored on
 // AccountManager at init; the program enforces only what is configured.
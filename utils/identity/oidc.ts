@@ -10,11 +10,11 @@ export const buildOidcIdentity = (
   {
     iss,
     aud,
-    emailHash,
+    subjectCommitment,
   }: {
     iss: string;
     aud: string;
-    emailHash: Uint8Array;
+    subjectCommitment: Uint8Array;
   },
   permissions: Permissions
 ) => {
@@ -24,7 +24,7 @@ export const buildOidcIdentity = (
         "0": {
           iss,
           aud,
-          emailHash: Array.from(emailHash),
+          subjectCommitment: Array.from(subjectCommitment),
         },
       },
     },
@@ -7,6 +7,8 @@ import {
   ABSTRACT_ACCOUNT_SEED,
   ACCOUNT_MANAGER_SEED,
   CHAIN_SIGNATURES_PROGRAM_ID,
+  NONCE_BITMAP_SEED,
+  PAYMASTER_SEED,
 } from "../utils/constants";
 
 export const findAbstractAccountPDA = (accountId: BN, programId: PublicKey) => {
@@ -20,6 +22,20 @@ export const findAccountManagerPDA = (programId: PublicKey) => {
   return PublicKey.findProgramAddressSync([ACCOUNT_MANAGER_SEED], programId);
 };
 
+export const findPaymasterPDA = (sponsor: PublicKey, programId: PublicKey) => {
+  return PublicKey.findProgramAddressSync(
+    [PAYMASTER_SEED, sponsor.toBuffer()],
+    programId
+  );
+};
+
+export const findNonceBitmapPDA = (accountId: BN, programId: PublicKey) => {
+  return PublicKey.findProgramAddressSync(
+    [NONCE_BITMAP_SEED, accountId.toArrayLike(Buffer, "le", 8)],
+    programId
+  );
+};
+
 export const cleanUpProgramState = async (
   program: anchor.Program<SolanaAa>,
   connection: anchor.web3.Connection,
@@ -49,12 +65,24 @@ export const cleanUpProgramState = async (
 
         try {
           const accountInfo = await connection.getAccountInfo(accountPDA);
+          const [nonceBitmapPDA] = findNonceBitmapPDA(
+            new BN(i),
+            program.programId
+          );
+          const nonceBitmapInfo = await connection.getAccountInfo(
+            nonceBitmapPDA
+          );
 
           if (accountInfo) {
+            const nonceBitmap = nonceBitmapInfo
+              ? await program.account.nonceBitmap.fetch(nonceBitmapPDA)
+              : null;
             const signature = await program.methods
               .deleteAccount(new BN(i))
-              .accounts({
+              .accountsPartial({
                 signer: provider.wallet.publicKey,
+                nonceBitmap: nonceBitmap ? nonceBitmapPDA : null,
+                nonceBitmapPayer: nonceBitmap?.payer ?? null,
               })
               .rpc();
 
@@ -1,9 +1,15 @@
 import * as anchor from "@coral-xyz/anchor";
 import { BN } from "@coral-xyz/anchor";
-import { AccountMeta } from "@solana/web3.js";
+import { AccountMeta, PublicKey } from "@solana/web3.js";
 import { Hex, keccak256 } from "viem";
 import { SolanaAa } from "../target/types/solana_aa";
-import { Action, borshUtils, Transaction } from "../borsh";
+import {
+  Action,
+  borshUtils,
+  ReplayProtection,
+  Transaction,
+  TransactionFee,
+} from "../borsh";
 import { signWithEthereum } from "./secp256k1-signer";
 import {
   createSecp256k1VerificationInstruction,
@@ -13,10 +19,19 @@ import {
 import { findAbstractAccountPDA } from "./program";
 import { confirmTransaction } from "./solana";
 
+/** Packs a keyed transaction nonce: `nonceKey << 64 | sequence`. */
+export function keyedNonce(nonceKey: bigint, sequence: bigint): bigint {
+  return (nonceKey << 64n) | sequence;
+}
+
 /**
  * Sign a `Transaction` with an Ethereum key and submit it through the
  * authenticated `execute_ek256` path (with the secp256k1 precompile
- * pre-instruction). Fetches the account's current nonce unless one is supplied.
+ * pre-instruction). Fetches the account's current nonce on `nonceKey` (default
+ * 0) unless one is supplied.
+ * With `paymaster`, the paymaster reimburses the wallet that submits it. An
+ * unordered `replayProtection` needs the account's `nonceBitmap` and a `nonce`.
+ * A `RemoveAccount` closes a passed `nonceBitmap` to its `nonceBitmapPayer`.
  */
 export async function executeEk256Action(
   program: anchor.Program<SolanaAa>,
@@ -25,7 +40,15 @@ export async function executeEk256Action(
     ethPrivateKey: Hex;
     action: Action;
     nonce?: bigint;
+    nonceKey?: bigint;
     remainingAccounts?: AccountMeta[];
+    paymaster?: PublicKey;
+    fee?: TransactionFee;
+    feeRecipient?: PublicKey;
+    relayer?: PublicKey;
+    replayProtection?: ReplayProtection;
+    nonceBitmap?: PublicKey;
+    nonceBitmapPayer?: PublicKey;
   }
 ): Promise<string> {
   let nonce = opts.nonce;
@@ -35,13 +58,23 @@ export async function executeEk256Action(
       program.programId
     );
     const accountInfo = await program.account.abstractAccount.fetch(accountPDA);
-    nonce = BigInt(accountInfo.nonce.toString());
+    const nonceKey = opts.nonceKey ?? 0n;
+    const sequence =
+      nonceKey === 0n
+        ? accountInfo.nonce
+        : accountInfo.nonceKeys.find(
+            (entry) => BigInt(entry.key.toString()) === nonceKey
+          )?.sequence ?? 0;
+    nonce = keyedNonce(nonceKey, BigInt(sequence.toString()));
   }
 
   const transaction: Transaction = {
     account_id: opts.accountId,
     nonce,
     action: opts.action,
+    fee: opts.fee ?? null,
+    relayer: opts.relayer?.toBytes() ?? null,
+    replay_protection: opts.replayProtection ?? { Sequential: {} },
   };
   const message = Buffer.from(borshUtils.serialize.transaction(transaction));
 
@@ -65,6 +98,14 @@ export async function executeEk256Action(
   if (opts.remainingAccounts) {
     builder.remainingAccounts(opts.remainingAccounts);
   }
+  if (opts.paymaster || opts.nonceBitmap || opts.feeRecipient) {
+    builder.accountsPartial({
+      paymaster: opts.paymaster ?? null,
+      nonceBitmap: opts.nonceBitmap ?? null,
+      nonceBitmapPayer: opts.nonceBitmapPayer ?? null,
+      feeRecipient: opts.feeRecipient ?? null,
+    });
+  }
 
   const txSignature = await builder.rpc();
   await confirmTransaction(program.provider.connection, txSignature);
@@ -0,0 +1,92 @@
+import * as anchor from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+import { createHash, randomBytes } from "crypto";
+import { SolanaAa } from "../target/types/solana_aa";
+import { UNIFIED_STORAGE_SEED } from "./constants";
+import { confirmTransaction } from "./solana";
+
+/** Size of every chunk but the last, which holds the remainder. */
+export const MAX_CHUNK_SIZE = 900;
+
+// Discriminator + `UnifiedStorage` header; the chunk bitmap and the payload
+// follow.
+const HEADER_SIZE = 8 + 248;
+// Most an instruction may grow an account by.
+const MAX_PERMITTED_DATA_INCREASE = 10_240;
+
+export const findUnifiedStoragePDA = (
+  owner: PublicKey,
+  dataId: Uint8Array,
+  programId: PublicKey
+) => {
+  return PublicKey.findProgramAddressSync(
+    [UNIFIED_STORAGE_SEED, owner.toBuffer(), Buffer.from(dataId)],
+    programId
+  );
+};
+
+/** Account size of a buffer holding `dataLen` bytes. */
+export const storageSpace = (dataLen: number) => {
+  const totalChunks = Math.ceil(dataLen / MAX_CHUNK_SIZE);
+  return HEADER_SIZE + Math.ceil(totalChunks / 8) + dataLen;
+};
+
+/**
+ * Uploads `data` into a fresh transaction buffer of the provider wallet and
+ * returns the buffer PDA. The account is grown to its full size first, then
+ * every chunk is written concurrently and the buffer is finalized.
+ */
+export const writeTransactionBuffer = async (
+  program: anchor.Program<SolanaAa>,
+  data: Uint8Array,
+  dataId: number[] = Array.from(randomBytes(32))
+) => {
+  const provider = program.provider as anchor.AnchorProvider;
+  const owner = provider.wallet.publicKey;
+  const dataHash = Array.from(createHash("sha256").update(data).digest());
+  const [unifiedStorage] = findUnifiedStoragePDA(
+    owner,
+    Uint8Array.from(dataId),
+    program.programId
+  );
+
+  const initSignature = await program.methods
+    .initStorage(dataId, [], data.length, dataHash)
+    .accounts({ owner, payer: owner })
+    .rpc();
+  await confirmTransaction(provider.connection, initSignature);
+
+  const space = storageSpace(data.length);
+  for (
+    let allocated = Math.min(space, MAX_PERMITTED_DATA_INCREASE);
+    allocated < space;
+    allocated += MAX_PERMITTED_DATA_INCREASE
+  ) {
+    const signature = await program.methods
+      .extendStorage()
+      .accounts({ unifiedStorage, writer: owner })
+      .rpc();
+    await confirmTransaction(provider.connection, signature);
+  }
+
+  const totalChunks = Math.ceil(data.length / MAX_CHUNK_SIZE);
+  await Promise.all(
+    Array.from({ length: totalChunks }, async (_, index) => {
+      const chunk = Buffer.from(
+        data.slice(index * MAX_CHUNK_SIZE, (index + 1) * MAX_CHUNK_SIZE)
+      );
+      const signature = await program.methods
+        .storeChunk(index, chunk)
+        .accounts({ unifiedStorage, writer: owner })
+        .rpc();
+      await confirmTransaction(provider.connection, signature);
+    })
+  );
+
+  const finalizeSignature = await program.methods
+    .finalizeStorage()
+    .accounts({ unifiedStorage, writer: owner })
+    .rpc();
+  await confirmTransaction(provider.connection, finalizeSignature);
+  return unifiedStorage;
+};
@@ -1,40 +1,94 @@
 import { PublicKey } from "@solana/web3.js";
+import { createHash } from "crypto";
 import * as fs from "fs";
 import * as path from "path";
-import { OIDC_KEY_REGISTRY_SEED } from "./constants";
+import { OIDC_KEY_REGISTRY_SEED, ZK_VKEY_REGISTRY_SEED } from "./constants";
+
+/** What the guest commits for one JWT, plus the `sub` and salt behind it. */
+export interface ZkOidcFixtureJwt {
+  subjectCommitment: string;
+  sub: string;
+  salt: string;
+  pkHash: string;
+  iss: string;
+  aud: string;
+  nonce: string;
+  iat: number;
+  exp: number;
+  alg: string;
+  nonceClaim: string;
+}
 
 /**
  * Golden fixture produced by `cd zk/script && cargo run --release -- fixture`.
  * Contains a real SP1 Groth16 proof over a self-signed test JWT whose nonce is
  * the sha256 of the Borsh-serialized test transaction.
  */
-export interface ZkOidcFixture {
+export interface ZkOidcFixture extends ZkOidcFixtureJwt {
   proof: string;
   publicValues: string;
   vkeyHash: string;
-  emailHash: string;
-  pkHash: string;
-  iss: string;
-  aud: string;
-  nonce: string;
   accountId: string;
   accountNonce: string;
   ethAddress: string;
   transactionBorsh: string;
 }
 
-export const loadZkOidcFixture = (name: string): ZkOidcFixture => {
+/**
+ * Golden fixture produced by `fixture --aggregate`: one `jwt-aggregate` proof
+ * over test JWTs of two subjects, all binding the same test transaction.
+ */
+export interface ZkOidcAggregateFixture {
+  proof: string;
+  publicValues: string;
+  vkeyHash: string;
+  accountId: string;
+  accountNonce: string;
+  ethAddress: string;
+  transactionBorsh: string;
+  jwts: ZkOidcFixtureJwt[];
+}
+
+export const loadZkOidcFixture = <T = ZkOidcFixture>(name: string): T => {
   const fixturePath = path.join(__dirname, "..", "tests", "fixtures", name);
-  return JSON.parse(fs.readFileSync(fixturePath, "utf-8")) as ZkOidcFixture;
+  return JSON.parse(fs.readFileSync(fixturePath, "utf-8")) as T;
+};
+
+/**
+ * The blinded OIDC identity committed by the guest program:
+ * sha256(salt || sub), with a 32-byte user-held salt.
+ */
+export const oidcSubjectCommitment = (salt: Uint8Array, sub: string) => {
+  return createHash("sha256").update(salt).update(sub).digest();
+};
+
+/** OIDC key registries are per issuer, seeded by sha256(iss). */
+export const oidcIssuerHash = (iss: string) => {
+  return createHash("sha256").update(iss).digest();
+};
+
+export const findOidcKeyRegistryPDA = (iss: string, programId: PublicKey) => {
+  return PublicKey.findProgramAddressSync(
+    [OIDC_KEY_REGISTRY_SEED, oidcIssuerHash(iss)],
+    programId
+  );
+};
+
+export const findZkVkeyRegistryPDA = (programId: PublicKey) => {
+  return PublicKey.findProgramAddressSync([ZK_VKEY_REGISTRY_SEED], programId);
 };
 
-export const findOidcKeyRegistryPDA = (programId: PublicKey) => {
-  return PublicKey.findProgramAddressSync([OIDC_KEY_REGISTRY_SEED], programId);
+/** `vkeyHash` is the 0x-prefixed `vk.bytes32()` string printed by zk/script. */
+export const vkeyHashBytes = (vkeyHash: string) => {
+  return Array.from(Buffer.from(vkeyHash.replace(/^0x/, ""), "hex"));
 };
 
-export const groth16ProofFromFixture = (fixture: ZkOidcFixture) => {
+export const groth16ProofFromFixture = (
+  fixture: Pick<ZkOidcFixture, "proof" | "publicValues" | "vkeyHash">
+) => {
   return {
     proof: Buffer.from(fixture.proof, "hex"),
     publicValues: Buffer.from(fixture.publicValues, "hex"),
+    vkeyHash: vkeyHashBytes(fixture.vkeyHash),
   };
 };
@@ -0,0 +1,19 @@
+//! Aggregating guest: proves several JWTs for `execute_zk_oidc_aggregated`, so
+//! multiple OIDC identities co-approve one transaction with one Groth16
+//! verification on-chain.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use zk_oidc_types::AggregatedOutputs;
+
+pub fn main() {
+    // Followed by that many single-JWT input sets (see `read_and_verify_jwt`).
+    let count = sp1_zkvm::io::read::<u32>();
+    assert!(count > 0, "no JWTs to aggregate");
+
+    let outputs = (0..count)
+        .map(|_| jwt_program::read_and_verify_jwt())
+        .collect();
+    sp1_zkvm::io::commit_slice(&AggregatedOutputs { outputs }.encode());
+}
@@ -0,0 +1,182 @@
+//! ZK guest programs: verify RS256-, ES256- or EdDSA-signed JWTs and commit
+//! privacy-preserving facts about them as public outputs.
+//!
+//! `jwt-program` (`main.rs`) proves one JWT. `jwt-aggregate`
+//! (`bin/jwt-aggregate.rs`) proves several in one proof, so multiple OIDC
+//! identities can co-approve a transaction for a single on-chain verification.
+//! Both run the verification below on each JWT.
+//!
+//! The user's `sub` claim is only committed blinded, as `sha256(salt || sub)`
+//! with a user-held 32-byte salt passed as a private input, so the on-chain
+//! identity cannot be linked back to a provider account by hashing guesses.
+//!
+//! The program fails closed — any invalid input (bad DER, bad signature, missing
+//! claims) panics, so a proof can only exist for a JWT whose signature verifies
+//! against the provided public key. Policy decisions (trusted issuer keys, identity
+//! membership, transaction binding) belong to the on-chain program.
+//!
+//! The JWT header `alg` selects the verifier, and `pk_der` (SPKI DER) must parse
+//! as that algorithm's key type, so a token can never be checked under a key of
+//! another kind. RSA, P-256, Ed25519 and SHA-256 use SP1's precompile-accelerated
+//! forks (see [patch.crates-io] in Cargo.toml), turning the dominant
+//! bigint/curve/hash work into syscalls.
+
+use base64::Engine;
+use rsa::{pkcs8::DecodePublicKey, Pkcs1v15Sign, RsaPublicKey};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use zk_oidc_types::PublicOutputs;
+
+/// JWT `alg` values this guest verifies. Committed as-is in `PublicOutputs`.
+const ALG_RS256: &str = "RS256";
+const ALG_ES256: &str = "ES256";
+const ALG_EDDSA: &str = "EdDSA";
+
+/// Standard OIDC claim carrying the request nonce.
+const NONCE_CLAIM: &str = "nonce";
+
+/// Reads one JWT's private inputs from stdin and verifies it, panicking on
+/// anything invalid. Inputs, in order: SPKI DER public key, base64url header,
+/// base64url payload, raw signature, 32-byte salt, nonce claim name.
+pub fn read_and_verify_jwt() -> PublicOutputs {
+    let pk_der = sp1_zkvm::io::read::<Vec<u8>>();
+    let jwt_header = sp1_zkvm::io::read::<Vec<u8>>();
+    let jwt_payload = sp1_zkvm::io::read::<Vec<u8>>();
+    let signature = sp1_zkvm::io::read::<Vec<u8>>();
+    let salt = sp1_zkvm::io::read::<[u8; 32]>();
+    let nonce_claim = sp1_zkvm::io::read::<String>();
+
+    let alg = extract_alg(&jwt_header).expect("invalid JWT header");
+
+    // JWT signing input: base64url(header) || '.' || base64url(payload)
+    let mut signing_input = jwt_header;
+    signing_input.push(b'.');
+    signing_input.extend_from_slice(&jwt_payload);
+
+    match alg.as_str() {
+        ALG_RS256 => verify_rs256(&pk_der, &signing_input, &signature),
+        ALG_ES256 => verify_es256(&pk_der, &signing_input, &signature),
+        ALG_EDDSA => verify_eddsa(&pk_der, &signing_input, &signature),
+        _ => panic!("unsupported JWT alg"),
+    }
+
+    let payload_bytes = decode_payload(&jwt_payload).expect("invalid JWT payload");
+    let claims = extract_claims(&payload_bytes).expect("invalid JWT claims");
+    assert!(claims.iat <= claims.exp, "JWT issued after it expires");
+    // Committed verbatim: any provider-side transformation (e.g. hashing) is
+    // undone on-chain per the issuer's registered nonce binding.
+    let nonce = extract_nonce(&payload_bytes, &claims, &nonce_claim).expect("missing nonce claim");
+
+    // The salt is fixed-length, so `salt || sub` is unambiguous without a
+    // separator. `sub` rather than `email`: it is stable and never reassigned.
+    let subject_commitment: [u8; 32] = Sha256::new()
+        .chain_update(salt)
+        .chain_update(claims.sub.as_bytes())
+        .finalize()
+        .into();
+    let pk_hash: [u8; 32] = Sha256::digest(&pk_der).into();
+
+    PublicOutputs {
+        subject_commitment,
+        pk_hash,
+        iss: claims.iss,
+        aud: claims.aud,
+        nonce,
+        iat: claims.iat,
+        exp: claims.exp,
+        alg,
+        nonce_claim,
+    }
+}
+
+fn verify_rs256(pk_der: &[u8], signing_input: &[u8], signature: &[u8]) {
+    let public_key = RsaPublicKey::from_public_key_der(pk_der).expect("invalid RSA public key DER");
+    let hashed_msg = Sha256::digest(signing_input);
+    public_key
+        .verify(Pkcs1v15Sign::new::<Sha256>(), &hashed_msg, signature)
+        .expect("RSA signature verification failed");
+}
+
+/// ES256: ECDSA over P-256 with SHA-256. JWS encodes the signature as the raw
+/// 64-byte `r || s`, not DER.
+fn verify_es256(pk_der: &[u8], signing_input: &[u8], signature: &[u8]) {
+    use p256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+
+    let public_key =
+        VerifyingKey::from_public_key_der(pk_der).expect("invalid P-256 public key DER");
+    let signature = Signature::from_slice(signature).expect("invalid ES256 signature encoding");
+    public_key
+        .verify(signing_input, &signature)
+        .expect("ES256 signature verification failed");
+}
+
+/// EdDSA: Ed25519 (RFC 8037). Strict verification rejects small-order keys
+/// and non-canonical signatures, so a signature cannot be malleated.
+fn verify_eddsa(pk_der: &[u8], signing_input: &[u8], signature: &[u8]) {
+    use ed25519_dalek::{pkcs8::DecodePublicKey, Signature, VerifyingKey};
+
+    let public_key =
+        VerifyingKey::from_public_key_der(pk_der).expect("invalid Ed25519 public key DER");
+    let signature = Signature::from_slice(signature).expect("invalid EdDSA signature encoding");
+    public_key
+        .verify_strict(signing_input, &signature)
+        .expect("EdDSA signature verification failed");
+}
+
+#[derive(Deserialize)]
+struct Header {
+    alg: String,
+}
+
+fn extract_alg(jwt_header: &[u8]) -> Result<String, &'static str> {
+    let header_bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(jwt_header)
+        .map_err(|_| "header base64 decode failed")?;
+    let header: Header = serde_json::from_slice(&header_bytes).map_err(|_| "header missing alg")?;
+    Ok(header.alg)
+}
+
+#[derive(Deserialize)]
+struct Claims {
+    iss: String,
+    aud: String,
+    sub: String,
+    #[serde(default)]
+    nonce: Option<String>,
+    iat: u64,
+    exp: u64,
+}
+
+fn decode_payload(jwt_payload: &[u8]) -> Result<Vec<u8>, &'static str> {
+    // jwt_payload is base64url(JSON). Decode straight from the b64 bytes — the
+    // base64 alphabet is ASCII, so a separate UTF-8 check would be redundant.
+    base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(jwt_payload)
+        .map_err(|_| "payload base64 decode failed")
+}
+
+fn extract_claims(payload_bytes: &[u8]) -> Result<Claims, &'static str> {
+    // Deserialize only the claims we consume directly into the struct: serde
+    // skips the other JWT fields (azp/email/…) without building a Value tree,
+    // and a missing or mistyped claim fails closed via the caller's expect.
+    serde_json::from_slice(payload_bytes).map_err(|_| "payload missing required claims")
+}
+
+fn extract_nonce(
+    payload_bytes: &[u8],
+    claims: &Claims,
+    nonce_claim: &str,
+) -> Result<String, &'static str> {
+    if nonce_claim == NONCE_CLAIM {
+        return claims.nonce.clone().ok_or("payload missing nonce claim");
+    }
+
+    // Custom claim: only issuers configured for one pay for the generic parse.
+    let payload: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_slice(payload_bytes).map_err(|_| "payload is not a JSON object")?;
+    payload
+        .get(nonce_claim)
+        .and_then(serde_json::Value::as_str)
+        .map(str::to_owned)
+        .ok_or("payload missing custom nonce claim")
+}
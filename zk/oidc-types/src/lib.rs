@@ -0,0 +1,368 @@
+//! Types shared by the ZK OIDC guest, the on-chain verifier and the host
+//! tooling.
+//!
+//! `PublicOutputs` is the one contract between the three: the guest commits it
+//! as the proof's public values and the program decodes it after verifying the
+//! proof. Defining it (and its encoding) once means a layout change breaks
+//! compilation everywhere instead of on-chain verification of new proofs.
+//!
+//! The encoding is a version byte followed by the fields in declaration order,
+//! laid out as bincode 1 does by default: fixed-size arrays raw, `u64`s
+//! little-endian, strings as a little-endian `u64` length plus UTF-8 bytes.
+//! It is hand-written so the crate stays `no_std` and dependency-free.
+//!
+//! `AggregatedOutputs` is the same for the aggregating guest, which verifies
+//! several JWTs in one proof: its own version byte, a `u64` count, then each
+//! `PublicOutputs` without its version byte.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Bumped on any change to `PublicOutputs` or its encoding. Decoders reject
+/// other versions, so a proof from a stale guest fails cleanly on-chain.
+pub const PUBLIC_OUTPUTS_VERSION: u8 = 1;
+
+/// Leading byte of `AggregatedOutputs`. Disjoint from `PUBLIC_OUTPUTS_VERSION`:
+/// both guests' vkeys sit in the same registry, so neither encoding may
+/// decode as the other.
+pub const AGGREGATED_OUTPUTS_VERSION: u8 = 0x80 | 1;
+
+/// Public outputs committed by the guest program.
+///
+/// `subject_commitment` and `pk_hash` are sha256: the precompile makes it
+/// ~100x cheaper in-guest than Poseidon2 (which has no precompile), and any
+/// external tooling can recompute it (e.g. a JWKS updater hashing key DERs).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicOutputs {
+    /// `sha256(salt || sub)`, blinding the provider account behind a user salt.
+    pub subject_commitment: [u8; 32],
+    /// `sha256` of the SPKI DER public key the JWT signature verified against.
+    pub pk_hash: [u8; 32],
+    pub iss: String,
+    pub aud: String,
+    pub nonce: String,
+    /// `iat`/`exp` claims (unix seconds), so the on-chain program can bound the
+    /// proof's lifetime to the token's.
+    pub iat: u64,
+    pub exp: u64,
+    /// JWT header `alg` the signature was verified under (RS256/ES256/EdDSA).
+    pub alg: String,
+    /// Name of the claim `nonce` was read from. `"nonce"` for most providers;
+    /// the on-chain registry pins which claim each issuer uses.
+    pub nonce_claim: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    UnsupportedVersion(u8),
+    UnexpectedEnd,
+    InvalidUtf8,
+    TrailingBytes,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::UnsupportedVersion(version) => {
+                write!(f, "unsupported public outputs version {version}")
+            }
+            DecodeError::UnexpectedEnd => f.write_str("public outputs truncated"),
+            DecodeError::InvalidUtf8 => f.write_str("public outputs string is not UTF-8"),
+            DecodeError::TrailingBytes => f.write_str("trailing bytes after public outputs"),
+        }
+    }
+}
+
+/// Public outputs of the aggregating guest: one entry per verified JWT, in
+/// input order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AggregatedOutputs {
+    pub outputs: Vec<PublicOutputs>,
+}
+
+impl PublicOutputs {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.push(PUBLIC_OUTPUTS_VERSION);
+        self.write_fields(&mut bytes);
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut reader = Reader { bytes };
+        reader.version(PUBLIC_OUTPUTS_VERSION)?;
+        let outputs = Self::read_fields(&mut reader)?;
+        reader.finish()?;
+        Ok(outputs)
+    }
+
+    fn write_fields(&self, bytes: &mut Vec<u8>) {
+        // Destructured so a new field fails to compile until it is encoded.
+        let PublicOutputs {
+            subject_commitment,
+            pk_hash,
+            iss,
+            aud,
+            nonce,
+            iat,
+            exp,
+            alg,
+            nonce_claim,
+        } = self;
+
+        bytes.extend_from_slice(subject_commitment);
+        bytes.extend_from_slice(pk_hash);
+        for field in [iss, aud, nonce] {
+            write_string(bytes, field);
+        }
+        bytes.extend_from_slice(&iat.to_le_bytes());
+        bytes.extend_from_slice(&exp.to_le_bytes());
+        write_string(bytes, alg);
+        write_string(bytes, nonce_claim);
+    }
+
+    fn read_fields(reader: &mut Reader<'_>) -> Result<Self, DecodeError> {
+        Ok(PublicOutputs {
+            subject_commitment: reader.array()?,
+            pk_hash: reader.array()?,
+            iss: reader.string()?,
+            aud: reader.string()?,
+            nonce: reader.string()?,
+            iat: reader.u64()?,
+            exp: reader.u64()?,
+            alg: reader.string()?,
+            nonce_claim: reader.string()?,
+        })
+    }
+}
+
+impl AggregatedOutputs {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.push(AGGREGATED_OUTPUTS_VERSION);
+        bytes.extend_from_slice(&(self.outputs.len() as u64).to_le_bytes());
+        for outputs in &self.outputs {
+            outputs.write_fields(&mut bytes);
+        }
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut reader = Reader { bytes };
+        reader.version(AGGREGATED_OUTPUTS_VERSION)?;
+        let count = reader.u64()?;
+        // Not preallocated from `count`: it is input, and every entry takes
+        // far more than one byte, so the loop runs out of input first.
+        let mut outputs = Vec::new();
+        for _ in 0..count {
+            outputs.push(PublicOutputs::read_fields(&mut reader)?);
+        }
+        reader.finish()?;
+        Ok(AggregatedOutputs { outputs })
+    }
+}
+
+/// The JWT nonce that binds a token to one transaction: lowercase hex of
+/// `sha256(borsh(transaction))`. Takes the digest so each caller hashes with
+/// its own backend (syscall on-chain, precompile in the guest, `sha2` on the
+/// host).
+pub fn transaction_nonce_hex(transaction_hash: &[u8; 32]) -> String {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+
+    let mut nonce = String::with_capacity(64);
+    for byte in transaction_hash {
+        nonce.push(HEX[usize::from(byte >> 4)] as char);
+        nonce.push(HEX[usize::from(byte & 0x0f)] as char);
+    }
+    nonce
+}
+
+fn write_string(bytes: &mut Vec<u8>, value: &str) {
+    bytes.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(value.as_bytes());
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn version(&mut self, expected: u8) -> Result<(), DecodeError> {
+        let [version] = self.array()?;
+        if version != expected {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+        Ok(())
+    }
+
+    fn finish(&self) -> Result<(), DecodeError> {
+        if !self.bytes.is_empty() {
+            return Err(DecodeError::TrailingBytes);
+        }
+        Ok(())
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        if self.bytes.len() < len {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        let (head, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn u64(&mut self) -> Result<u64, DecodeError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    fn string(&mut self) -> Result<String, DecodeError> {
+        let len = usize::try_from(self.u64()?).map_err(|_| DecodeError::UnexpectedEnd)?;
+        let bytes = self.take(len)?;
+        let value = core::str::from_utf8(bytes).map_err(|_| DecodeError::InvalidUtf8)?;
+        Ok(String::from(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn outputs(sub_byte: u8) -> PublicOutputs {
+        PublicOutputs {
+            subject_commitment: [sub_byte; 32],
+            pk_hash: [0xab; 32],
+            iss: String::from("https://issuer.example"),
+            aud: String::from("client-id"),
+            nonce: transaction_nonce_hex(&[0x5a; 32]),
+            iat: 1_700_000_000,
+            exp: 1_700_003_600,
+            alg: String::from("RS256"),
+            nonce_claim: String::from("nonce"),
+        }
+    }
+
+    #[test]
+    fn public_outputs_round_trip() {
+        let outputs = outputs(1);
+        let bytes = outputs.encode();
+        assert_eq!(bytes[0], PUBLIC_OUTPUTS_VERSION);
+        assert_eq!(&bytes[1..33], &[1u8; 32]);
+        assert_eq!(PublicOutputs::decode(&bytes), Ok(outputs));
+    }
+
+    #[test]
+    fn aggregated_outputs_round_trip() {
+        let aggregated = AggregatedOutputs {
+            outputs: vec![outputs(1), outputs(2)],
+        };
+        let bytes = aggregated.encode();
+        assert_eq!(bytes[0], AGGREGATED_OUTPUTS_VERSION);
+        assert_eq!(&bytes[1..9], &2u64.to_le_bytes());
+        assert_eq!(AggregatedOutputs::decode(&bytes), Ok(aggregated));
+
+        let empty = AggregatedOutputs { outputs: vec![] };
+        assert_eq!(AggregatedOutputs::decode(&empty.encode()), Ok(empty));
+    }
+
+    #[test]
+    fn aggregated_entries_match_single_encoding() {
+        let single = outputs(3).encode();
+        let aggregated = AggregatedOutputs {
+            outputs: vec![outputs(3)],
+        }
+        .encode();
+        assert_eq!(&aggregated[9..], &single[1..]);
+    }
+
+    #[test]
+    fn rejects_unknown_version() {
+        let mut bytes = outputs(1).encode();
+        bytes[0] = PUBLIC_OUTPUTS_VERSION + 1;
+        assert_eq!(
+            PublicOutputs::decode(&bytes),
+            Err(DecodeError::UnsupportedVersion(PUBLIC_OUTPUTS_VERSION + 1))
+        );
+    }
+
+    #[test]
+    fn rejects_the_other_guests_encoding() {
+        let single = outputs(1).encode();
+        let aggregated = AggregatedOutputs {
+            outputs: vec![outputs(1)],
+        }
+        .encode();
+
+        assert_eq!(
+            AggregatedOutputs::decode(&single),
+            Err(DecodeError::UnsupportedVersion(PUBLIC_OUTPUTS_VERSION))
+        );
+        assert_eq!(
+            PublicOutputs::decode(&aggregated),
+            Err(DecodeError::UnsupportedVersion(AGGREGATED_OUTPUTS_VERSION))
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_input() {
+        let single = outputs(1).encode();
+        for len in 0..single.len() {
+            assert_eq!(
+                PublicOutputs::decode(&single[..len]),
+                Err(DecodeError::UnexpectedEnd),
+                "length {len}"
+            );
+        }
+
+        let aggregated = AggregatedOutputs {
+            outputs: vec![outputs(1), outputs(2)],
+        }
+        .encode();
+        for len in 0..aggregated.len() {
+            assert_eq!(
+                AggregatedOutputs::decode(&aggregated[..len]),
+                Err(DecodeError::UnexpectedEnd),
+                "length {len}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_trailing_bytes() {
+        let mut single = outputs(1).encode();
+        single.push(0);
+        assert_eq!(
+            PublicOutputs::decode(&single),
+            Err(DecodeError::TrailingBytes)
+        );
+
+        let mut aggregated = AggregatedOutputs {
+            outputs: vec![outputs(1)],
+        }
+        .encode();
+        aggregated.push(0);
+        assert_eq!(
+            AggregatedOutputs::decode(&aggregated),
+            Err(DecodeError::TrailingBytes)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_utf8() {
+        let mut bytes = outputs(1).encode();
+        // First byte of `iss`, after the version, two hashes and its length.
+        bytes[1 + 32 + 32 + 8] = 0xff;
+        assert_eq!(PublicOutputs::decode(&bytes), Err(DecodeError::InvalidUtf8));
+    }
+}
@@ -0,0 +1,232 @@
+//! JWKS → `OidcKeyRegistry` entries.
+//!
+//! Each JWK is re-encoded as the SPKI DER the prover hands the guest, so the
+//! `pk_hash` printed here is exactly the one a proof against that key commits.
+
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use rsa::{pkcs8::EncodePublicKey, BigUint, RsaPublicKey};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use solana_aa::types::oidc_key_registry::{OidcKeyEntry, OidcKeyRegistry, OidcKeyType};
+
+#[derive(Deserialize)]
+pub struct Jwks {
+    pub keys: Vec<Jwk>,
+}
+
+/// The JWK members needed to rebuild RSA, P-256 and Ed25519 public keys.
+#[derive(Deserialize)]
+pub struct Jwk {
+    pub kty: String,
+    #[serde(default)]
+    pub kid: Option<String>,
+    #[serde(rename = "use", default)]
+    pub key_use: Option<String>,
+    #[serde(default)]
+    pub crv: Option<String>,
+    #[serde(default)]
+    pub n: Option<String>,
+    #[serde(default)]
+    pub e: Option<String>,
+    #[serde(default)]
+    pub x: Option<String>,
+    #[serde(default)]
+    pub y: Option<String>,
+}
+
+impl Jwk {
+    /// Key type and SPKI DER of this key, as the guest parses and hashes it.
+    pub fn to_spki_der(&self) -> Result<(OidcKeyType, Vec<u8>)> {
+        match (self.kty.as_str(), self.crv.as_deref()) {
+            ("RSA", _) => {
+                let n = BigUint::from_bytes_be(&self.member("n", &self.n)?);
+                let e = BigUint::from_bytes_be(&self.member("e", &self.e)?);
+                let der = RsaPublicKey::new(n, e)?.to_public_key_der()?;
+                Ok((OidcKeyType::Rsa, der.to_vec()))
+            }
+            ("EC", Some("P-256")) => {
+                // Uncompressed SEC1 point: 0x04 || x || y.
+                let mut point = vec![0x04];
+                point.extend(self.member("x", &self.x)?);
+                point.extend(self.member("y", &self.y)?);
+                let der = p256::PublicKey::from_sec1_bytes(&point)
+                    .context("invalid P-256 point")?
+                    .to_public_key_der()?;
+                Ok((OidcKeyType::P256, der.to_vec()))
+            }
+            ("OKP", Some("Ed25519")) => {
+                let x: [u8; 32] = self
+                    .member("x", &self.x)?
+                    .try_into()
+                    .map_err(|_| anyhow::anyhow!("Ed25519 x must be 32 bytes"))?;
+                let der = ed25519_dalek::VerifyingKey::from_bytes(&x)
+                    .context("invalid Ed25519 key")?
+                    .to_public_key_der()?;
+                Ok((OidcKeyType::Ed25519, der.to_vec()))
+            }
+            (kty, crv) => bail!("unsupported JWK kty={kty} crv={crv:?}"),
+        }
+    }
+
+    /// Encryption keys never sign ID tokens, so they are never registered.
+    pub fn is_signing_key(&self) -> bool {
+        self.key_use.as_deref() != Some("enc")
+    }
+
+    fn member(&self, name: &str, value: &Option<String>) -> Result<Vec<u8>> {
+        let value = value
+            .as_deref()
+            .with_context(|| format!("JWK {:?} missing `{name}`", self.kid))?;
+        base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(value)
+            .with_context(|| format!("JWK {:?} `{name}` is not base64url", self.kid))
+    }
+}
+
+/// Registry entries for the signing keys of `jwks`, valid from registration
+/// with no scheduled expiry.
+pub fn registry_entries(jwks: &Jwks) -> Result<Vec<OidcKeyEntry>> {
+    jwks.keys
+        .iter()
+        .filter(|jwk| jwk.is_signing_key())
+        .map(|jwk| {
+            let kid = jwk.kid.clone().unwrap_or_default();
+            // The program refuses longer kids; fail here rather than emit an
+            // `add_oidc_key` that cannot land.
+            if kid.len() > OidcKeyRegistry::MAX_KID_LEN {
+                bail!(
+                    "JWK kid {kid:?} exceeds {} bytes",
+                    OidcKeyRegistry::MAX_KID_LEN
+                );
+            }
+            let (key_type, der) = jwk.to_spki_der()?;
+            Ok(OidcKeyEntry {
+                pk_hash: Sha256::digest(&der).into(),
+                key_type,
+                kid,
+                not_before: 0,
+                not_after: u64::MAX,
+            })
+        })
+        .collect()
+}
+
+/// Keys to add to and remove from `registry` so it pins exactly `entries`.
+pub struct RegistryDiff<'a> {
+    pub add: Vec<&'a OidcKeyEntry>,
+    pub remove: Vec<&'a OidcKeyEntry>,
+    pub unchanged: Vec<&'a OidcKeyEntry>,
+}
+
+pub fn diff<'a>(entries: &'a [OidcKeyEntry], registry: &'a OidcKeyRegistry) -> RegistryDiff<'a> {
+    let (unchanged, add): (Vec<_>, Vec<_>) = entries
+        .iter()
+        .partition(|entry| registry.contains(&entry.pk_hash));
+    let remove = registry
+        .keys
+        .iter()
+        .filter(|key| !entries.iter().any(|entry| entry.pk_hash == key.pk_hash))
+        .collect();
+
+    RegistryDiff {
+        add,
+        remove,
+        unchanged,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_aa::types::oidc_key_registry::OidcNonceBinding;
+
+    // RFC 7517 appendix A.1 RSA and P-256 keys, RFC 8037 appendix A.2 Ed25519
+    // key. Expected hashes are of the SPKI DER as encoded by OpenSSL.
+    const RSA_JWK: &str = r#"{"kty":"RSA","kid":"2011-04-29","n":"0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw","e":"AQAB"}"#;
+    const RSA_PK_HASH: &str = "ad32320cf6c596d884b05381ba573aba8ddd5749b4de8f4a23a79f9a89ddaeb2";
+
+    const P256_JWK: &str = r#"{"kty":"EC","crv":"P-256","kid":"1","use":"sig","x":"MKBCTNIcKUSDii11ySs3526iDZ8AiTo7Tu6KPAqv7D4","y":"4Etl6SRW2YiLUrN5vfvVHuhp7x8PxltmWWlbbM4IFyM"}"#;
+    const P256_PK_HASH: &str = "51b944cdfa544d4c3273aa6bf350625a7dd53bbb6a71723274f538b19a207760";
+
+    const ED25519_JWK: &str = r#"{"kty":"OKP","crv":"Ed25519","kid":"ed","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#;
+    const ED25519_PK_HASH: &str =
+        "06e3fd8fda29bb60ab59557de61edb0aecdb231134be30e75b455f8e1b792fa9";
+
+    fn jwks(keys: &[&str]) -> Jwks {
+        serde_json::from_str(&format!(r#"{{"keys":[{}]}}"#, keys.join(","))).unwrap()
+    }
+
+    fn registry(keys: Vec<OidcKeyEntry>) -> OidcKeyRegistry {
+        OidcKeyRegistry {
+            authorities: vec![],
+            threshold: 1,
+            pending_authorities: vec![],
+            pending_threshold: 0,
+            key_add_delay: 0,
+            iss_hash: [0; 32],
+            iss: String::from("https://issuer.example"),
+            clock_skew: 0,
+            nonce_binding: OidcNonceBinding::Raw,
+            keys,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn converts_known_keys() {
+        let entries = registry_entries(&jwks(&[RSA_JWK, P256_JWK, ED25519_JWK])).unwrap();
+
+        let expected = [
+            (OidcKeyType::Rsa, "2011-04-29", RSA_PK_HASH),
+            (OidcKeyType::P256, "1", P256_PK_HASH),
+            (OidcKeyType::Ed25519, "ed", ED25519_PK_HASH),
+        ];
+        assert_eq!(entries.len(), expected.len());
+        for (entry, (key_type, kid, pk_hash)) in entries.iter().zip(expected) {
+            assert_eq!(entry.key_type, key_type);
+            assert_eq!(entry.kid, kid);
+            assert_eq!(hex::encode(entry.pk_hash), pk_hash);
+            assert_eq!((entry.not_before, entry.not_after), (0, u64::MAX));
+        }
+    }
+
+    #[test]
+    fn skips_encryption_keys() {
+        let enc = RSA_JWK.replace(r#""kty":"RSA","#, r#""kty":"RSA","use":"enc","#);
+        let entries = registry_entries(&jwks(&[&enc, ED25519_JWK])).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].key_type, OidcKeyType::Ed25519);
+    }
+
+    #[test]
+    fn rejects_kid_the_program_would_refuse() {
+        let max = "k".repeat(OidcKeyRegistry::MAX_KID_LEN);
+        let at_limit = ED25519_JWK.replace(r#""kid":"ed""#, &format!(r#""kid":"{max}""#));
+        assert!(registry_entries(&jwks(&[&at_limit])).is_ok());
+
+        let too_long = ED25519_JWK.replace(r#""kid":"ed""#, &format!(r#""kid":"{max}k""#));
+        assert!(registry_entries(&jwks(&[&too_long])).is_err());
+    }
+
+    #[test]
+    fn rejects_unsupported_and_malformed_keys() {
+        let p384 = P256_JWK.replace("P-256", "P-384");
+        assert!(registry_entries(&jwks(&[&p384])).is_err());
+
+        let short_x = r#"{"kty":"OKP","crv":"Ed25519","x":"AAAA"}"#;
+        assert!(registry_entries(&jwks(&[short_x])).is_err());
+    }
+
+    #[test]
+    fn diffs_against_registry() {
+        let entries = registry_entries(&jwks(&[RSA_JWK, P256_JWK])).unwrap();
+        let rotated_out = registry_entries(&jwks(&[ED25519_JWK])).unwrap().remove(0);
+        let registry = registry(vec![entries[0].clone(), rotated_out.clone()]);
+
+        let diff = diff(&entries, &registry);
+        assert_eq!(diff.unchanged, vec![&entries[0]]);
+        assert_eq!(diff.add, vec![&entries[1]]);
+        assert_eq!(diff.remove, vec![&rotated_out]);
+    }
+}
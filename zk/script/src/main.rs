@@ -1,12 +1,25 @@
 //! Host-side tooling for the ZK OIDC path.
 //!
-//! - `vkey`: print the guest program's verification key hash, pinned on-chain as
-//!   `JWT_VKEY_HASH` in `programs/solana-aa/src/contract/auth/zk_oidc.rs`.
+//! - `vkey`: print the guest program's verification key hash (`--aggregate` for
+//!   the aggregating guest), registered on-chain through `add_zk_vkey` in the
+//!   `ZkVkeyRegistry`.
 //! - `fixture`: build the canonical test `Transaction` with the program's own Borsh
 //!   types, self-sign a JWT whose `nonce` is `hex(sha256(borsh(transaction)))`, run
 //!   the guest (execute or Groth16 prove), and emit a JSON fixture for the TS tests.
+//!   `--aggregate` signs JWTs for two subjects and proves them with the aggregating
+//!   guest instead.
+//! - `prove`: prove a real provider-issued JWT bound to a given `Transaction` and
+//!   emit the `Sp1Groth16Proof` for `execute_zk_oidc`; several JWTs give one
+//!   aggregated proof for `execute_zk_oidc_aggregated`.
+//! - `verify`: check a proof JSON (single or aggregated) offline with the
+//!   program's own verifier and run the `execute_zk_oidc` binding checks against
+//!   a transaction and registry.
+//! - `jwks`: turn a provider JWKS file into `OidcKeyRegistry` entries, optionally
+//!   diffed against a dumped registry account, for reviewable key rotation.
 
-use anchor_lang::AnchorSerialize;
+mod jwks;
+
+use anchor_lang::{AccountDeserialize, AnchorDeserialize, AnchorSerialize};
 use anyhow::{Context, Result};
 use base64::Engine;
 use clap::{Parser, Subcommand};
@@ -14,30 +27,42 @@ use rsa::{
     pkcs8::{DecodePrivateKey, EncodePublicKey},
     Pkcs1v15Sign, RsaPrivateKey, RsaPublicKey,
 };
-use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use sp1_sdk::{include_elf, HashableKey, ProverClient, SP1Stdin};
+use sp1_sdk::{
+    include_elf, HashableKey, ProverClient, SP1ProofWithPublicValues, SP1Stdin, SP1VerifyingKey,
+};
+use zk_oidc_types::{
+    transaction_nonce_hex, AggregatedOutputs, PublicOutputs, AGGREGATED_OUTPUTS_VERSION,
+};
 use solana_aa::types::{
     identity::{wallet::WalletType, Identity, IdentityPermissions, IdentityWithPermissions},
-    transaction::transaction::{Action, Transaction},
+    oidc_key_registry::{OidcKeyEntry, OidcKeyRegistry, OidcNonceBinding},
+    transaction::transaction::{Action, ReplayProtection, Transaction},
+};
+use solana_aa::zk_oidc::{
+    check_oidc_registry, verify_zk_oidc_aggregated_proof, verify_zk_oidc_proof, Sp1Groth16Proof,
 };
 
 const JWT_PROGRAM_ELF: &[u8] = include_elf!("jwt-program");
+const JWT_AGGREGATE_ELF: &[u8] = include_elf!("jwt-aggregate");
 
 const TEST_RSA_PRIVATE_KEY_PEM: &str = include_str!("../fixtures/test_rsa_private.pem");
 const TEST_ISS: &str = "https://test-issuer.solana-aa.dev";
 const TEST_AUD: &str = "solana-aa-tests";
 const TEST_EMAIL: &str = "test@solana-aa.dev";
-
-/// Mirror of the guest program's `PublicOutputs` (bincode field order must match).
-#[derive(Serialize, Deserialize, Debug)]
-struct PublicOutputs {
-    email_hash: [u8; 32],
-    pk_hash: [u8; 32],
-    iss: String,
-    aud: String,
-    nonce: String,
-}
+const TEST_SUB: &str = "test-subject-1";
+/// User salt blinding the `sub` commitment. A real client derives or stores
+/// this per user; the fixture only needs it to be fixed.
+const TEST_SALT: [u8; 32] = [0x5a; 32];
+/// Second subject of the aggregated fixture, co-approving with `TEST_SUB`.
+const TEST_SUB_2: &str = "test-subject-2";
+const TEST_SALT_2: [u8; 32] = [0xa5; 32];
+/// Claim the test JWT carries the transaction nonce in (`OidcNonceBinding::Raw`).
+const NONCE_CLAIM: &str = "nonce";
+const TEST_IAT: u64 = 1749700000;
+/// 2100-01-01T00:00:00Z. The on-chain program rejects expired tokens against
+/// the validator `Clock`, so the golden fixture must outlive any test run.
+const TEST_EXP: u64 = 4102444800;
 
 #[derive(Parser)]
 #[command(about = "ZK OIDC host tooling for solana-aa")]
@@ -48,10 +73,18 @@ struct Cli {
 
 #[derive(Subcommand)]
 enum Command {
-    /// Print the guest program verification key hash (pin as JWT_VKEY_HASH on-chain)
-    Vkey,
+    /// Print the guest program verification key hash (register with add_zk_vkey)
+    Vkey {
+        /// Print the aggregating guest's vkey instead
+        #[arg(long)]
+        aggregate: bool,
+    },
     /// Generate the golden test fixture (JWT -> SP1 proof -> JSON)
     Fixture {
+        /// Prove two JWTs of different subjects with the aggregating guest, for
+        /// `execute_zk_oidc_aggregated`
+        #[arg(long)]
+        aggregate: bool,
         /// Abstract account id the test transaction targets
         #[arg(long, default_value_t = 0)]
         account_id: u64,
@@ -64,35 +97,150 @@ enum Command {
         /// Only execute the guest (fast); skip Groth16 proving and fixture output
         #[arg(long)]
         execute_only: bool,
-        /// Output path for the fixture JSON
-        #[arg(long, default_value = "../../tests/fixtures/zk-oidc-add-identity.json")]
+        /// Output path for the fixture JSON [default:
+        /// ../../tests/fixtures/zk-oidc-add-identity.json, or
+        /// ../../tests/fixtures/zk-oidc-aggregate-add-identity.json with --aggregate]
+        #[arg(long)]
+        out: Option<String>,
+    },
+    /// Prove real JWTs bound to a transaction (JWT -> SP1 proof -> JSON). Repeat
+    /// --jwt/--key/--salt (and optionally --nonce-binding) per JWT to aggregate
+    /// several into one proof
+    Prove {
+        /// The compact JWT (`header.payload.signature`), or `@<path>` to read it
+        #[arg(long, required = true)]
+        jwt: Vec<String>,
+        /// Signing key: a JWK JSON file or an SPKI `PUBLIC KEY` PEM file
+        #[arg(long, required = true)]
+        key: Vec<String>,
+        /// The Borsh `Transaction` the JWT nonce binds: hex, or a fixture-style
+        /// JSON file carrying `transactionBorsh`
+        #[arg(long)]
+        transaction: String,
+        /// 32-byte hex salt blinding the `sub` commitment
+        #[arg(long, required = true)]
+        salt: Vec<String>,
+        /// The issuer registry's nonce binding: `raw`, `sha256` or `claim:<name>`.
+        /// Once for all JWTs, or once per JWT [default: raw]
+        #[arg(long, value_parser = parse_nonce_binding)]
+        nonce_binding: Vec<OidcNonceBinding>,
+        /// Only execute the guest (fast, reports cycles); skip Groth16 proving
+        #[arg(long)]
+        execute_only: bool,
+        /// Output path for the proof JSON
+        #[arg(long, default_value = "proof.json")]
         out: String,
     },
+    /// Verify a proof JSON offline, as execute_zk_oidc would, before submitting it
+    Verify {
+        /// Proof JSON (`proof`, `publicValues`, `vkeyHash`), e.g. from `prove`
+        path: String,
+        /// The Borsh `Transaction` to check the nonce binding against: hex, or a
+        /// JSON file carrying `transactionBorsh` [default: the proof JSON's own]
+        #[arg(long)]
+        transaction: Option<String>,
+        /// Registry account data to check issuer, key and lifetime against,
+        /// dumped with `solana account <registry PDA> --output-file <file>`.
+        /// Once for all JWTs, or once per JWT of an aggregated proof
+        #[arg(long)]
+        registry: Vec<String>,
+        /// Unix time to check key validity and token lifetime at [default: now]
+        #[arg(long)]
+        now: Option<u64>,
+    },
+    /// Compute OidcKeyRegistry entries (pk_hash per key) from a JWKS file
+    Jwks {
+        /// JWKS JSON file, e.g. saved from the provider's `jwks_uri`
+        path: String,
+        /// Issuer the JWKS belongs to (selects the registry PDA)
+        #[arg(long)]
+        iss: String,
+        /// Registry account data to diff against, dumped with
+        /// `solana account <registry PDA> --output-file <file>`
+        #[arg(long)]
+        registry: Option<String>,
+        /// Output path for the entries as JSON
+        #[arg(long)]
+        out: Option<String>,
+    },
 }
 
 fn main() -> Result<()> {
     sp1_sdk::utils::setup_logger();
 
     match Cli::parse().command {
-        Command::Vkey => vkey(),
+        Command::Vkey { aggregate } => vkey(aggregate),
         Command::Fixture {
+            aggregate,
             account_id,
             account_nonce,
             eth_address,
             execute_only,
             out,
-        } => fixture(account_id, account_nonce, &eth_address, execute_only, &out),
+        } => {
+            let out = out.unwrap_or_else(|| {
+                let name = if aggregate {
+                    "zk-oidc-aggregate-add-identity.json"
+                } else {
+                    "zk-oidc-add-identity.json"
+                };
+                format!("../../tests/fixtures/{name}")
+            });
+            fixture(
+                aggregate,
+                account_id,
+                account_nonce,
+                &eth_address,
+                execute_only,
+                &out,
+            )
+        }
+        Command::Prove {
+            jwt,
+            key,
+            transaction,
+            salt,
+            nonce_binding,
+            execute_only,
+            out,
+        } => prove(
+            &jwt,
+            &key,
+            &transaction,
+            &salt,
+            &nonce_binding,
+            execute_only,
+            &out,
+        ),
+        Command::Verify {
+            path,
+            transaction,
+            registry,
+            now,
+        } => verify(&path, transaction.as_deref(), &registry, now),
+        Command::Jwks {
+            path,
+            iss,
+            registry,
+            out,
+        } => jwks(&path, &iss, registry.as_deref(), out.as_deref()),
     }
 }
 
-fn vkey() -> Result<()> {
+fn vkey(aggregate: bool) -> Result<()> {
     let client = ProverClient::from_env();
-    let (_, vk) = client.setup(JWT_PROGRAM_ELF);
+    let elf = if aggregate {
+        JWT_AGGREGATE_ELF
+    } else {
+        JWT_PROGRAM_ELF
+    };
+    let (_, vk) = client.setup(elf);
     println!("{}", vk.bytes32());
     Ok(())
 }
 
 fn fixture(
+    aggregate: bool,
     account_id: u64,
     account_nonce: u128,
     eth_address: &str,
@@ -113,27 +261,398 @@ fn fixture(
                 enable_act_as: true,
             }),
         }),
+        fee: None,
+        relayer: None,
+        replay_protection: ReplayProtection::Sequential,
     };
 
     let transaction_bytes = transaction.try_to_vec()?;
-    let jwt_nonce = hex::encode(Sha256::digest(&transaction_bytes));
+    let jwt_nonce = transaction_nonce_hex(&Sha256::digest(&transaction_bytes).into());
     println!("transaction borsh: {}", hex::encode(&transaction_bytes));
     println!("jwt nonce (sha256): {jwt_nonce}");
 
     let private_key = RsaPrivateKey::from_pkcs8_pem(TEST_RSA_PRIVATE_KEY_PEM)?;
-    let public_key = RsaPublicKey::from(&private_key);
-    let pk_der = public_key.to_public_key_der()?.to_vec();
+    let subjects: &[(&str, [u8; 32])] = if aggregate {
+        &[(TEST_SUB, TEST_SALT), (TEST_SUB_2, TEST_SALT_2)]
+    } else {
+        &[(TEST_SUB, TEST_SALT)]
+    };
+    let inputs = subjects
+        .iter()
+        .map(|(sub, salt)| test_jwt_inputs(&private_key, sub, *salt, &jwt_nonce))
+        .collect::<Result<Vec<_>>>()?;
+
+    let elf = if aggregate {
+        JWT_AGGREGATE_ELF
+    } else {
+        JWT_PROGRAM_ELF
+    };
+    let stdin = guest_stdin(&inputs);
+
+    if execute_only {
+        let outputs = decode_outputs(&execute_guest(elf, &stdin)?)?;
+        anyhow::ensure!(
+            outputs.iter().all(|outputs| outputs.nonce == jwt_nonce),
+            "guest nonce mismatch"
+        );
+        return Ok(());
+    }
+
+    let (proof, vk) = prove_guest(elf, &stdin)?;
+    let outputs = decode_outputs(proof.public_values.as_slice())?;
+    anyhow::ensure!(
+        outputs.iter().all(|outputs| outputs.nonce == jwt_nonce),
+        "guest nonce mismatch"
+    );
+
+    let mut fixture = serde_json::json!({
+        "proof": hex::encode(proof.bytes()),
+        "publicValues": hex::encode(proof.public_values.to_vec()),
+        "vkeyHash": vk.bytes32(),
+        "accountId": account_id.to_string(),
+        "accountNonce": account_nonce.to_string(),
+        "ethAddress": eth_address,
+        "transactionBorsh": hex::encode(&transaction_bytes),
+    });
+    // The subject and salt are not public values, but the tests need them to
+    // recompute each commitment.
+    let mut jwts = outputs
+        .iter()
+        .zip(subjects)
+        .map(|(outputs, (sub, salt))| {
+            let mut jwt = outputs_json(outputs);
+            json_merge(
+                &mut jwt,
+                serde_json::json!({ "sub": sub, "salt": hex::encode(salt) }),
+            );
+            jwt
+        })
+        .collect::<Vec<_>>();
+    if aggregate {
+        fixture["jwts"] = jwts.into();
+    } else {
+        json_merge(&mut fixture, jwts.remove(0));
+    }
+
+    std::fs::write(out, serde_json::to_string_pretty(&fixture)?)?;
+    println!("fixture written to {out} (proof size: {} bytes)", proof.bytes().len());
+    Ok(())
+}
+
+fn prove(
+    jwts: &[String],
+    keys: &[String],
+    transaction: &str,
+    salts: &[String],
+    nonce_bindings: &[OidcNonceBinding],
+    execute_only: bool,
+    out: &str,
+) -> Result<()> {
+    anyhow::ensure!(
+        keys.len() == jwts.len() && salts.len() == jwts.len(),
+        "pass one --key and one --salt per --jwt"
+    );
+    anyhow::ensure!(
+        nonce_bindings.len() <= 1 || nonce_bindings.len() == jwts.len(),
+        "pass --nonce-binding once, or once per --jwt"
+    );
+
+    let transaction_bytes = read_transaction(transaction)?;
+    let transaction = Transaction::try_from_slice(&transaction_bytes)
+        .context("transaction is not a Borsh Transaction")?;
+    println!(
+        "transaction: account {} nonce {}",
+        transaction.account_id, transaction.nonce
+    );
+    let jwt_nonce = transaction_nonce_hex(&Sha256::digest(&transaction_bytes).into());
+
+    let inputs = (0..jwts.len())
+        .map(|index| {
+            let nonce_binding = nonce_bindings
+                .get(index)
+                .or(nonce_bindings.first())
+                .unwrap_or(&OidcNonceBinding::Raw);
+            jwt_inputs(
+                &jwts[index],
+                &keys[index],
+                &salts[index],
+                nonce_binding,
+                &jwt_nonce,
+            )
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    // One JWT keeps the single-JWT guest and `execute_zk_oidc`; more need the
+    // aggregating guest and `execute_zk_oidc_aggregated`.
+    let elf = if inputs.len() == 1 {
+        JWT_PROGRAM_ELF
+    } else {
+        JWT_AGGREGATE_ELF
+    };
+    let stdin = guest_stdin(&inputs);
+
+    if execute_only {
+        decode_outputs(&execute_guest(elf, &stdin)?)?;
+        return Ok(());
+    }
+
+    let (proof, vk) = prove_guest(elf, &stdin)?;
+    let outputs = decode_outputs(proof.public_values.as_slice())?;
+
+    // `proof`/`publicValues`/`vkeyHash` are the `Sp1Groth16Proof` fields, named
+    // as in the fixture so the TS `groth16ProofFromFixture` helper reads both.
+    let mut document = serde_json::json!({
+        "proof": hex::encode(proof.bytes()),
+        "publicValues": hex::encode(proof.public_values.to_vec()),
+        "vkeyHash": vk.bytes32(),
+        "transactionBorsh": hex::encode(&transaction_bytes),
+    });
+    match &outputs[..] {
+        [outputs] => json_merge(&mut document, outputs_json(outputs)),
+        _ => document["jwts"] = outputs.iter().map(outputs_json).collect(),
+    }
+    std::fs::write(out, serde_json::to_string_pretty(&document)?)?;
+    println!("proof written to {out}");
+    Ok(())
+}
+
+/// Guest inputs for one real JWT, after checking locally that its nonce claim
+/// binds the transaction (as execute_zk_oidc will) before minutes of proving.
+fn jwt_inputs(
+    jwt: &str,
+    key: &str,
+    salt: &str,
+    nonce_binding: &OidcNonceBinding,
+    jwt_nonce: &str,
+) -> Result<JwtInputs> {
+    let jwt = match jwt.strip_prefix('@') {
+        Some(path) => std::fs::read_to_string(path).with_context(|| format!("reading {path}"))?,
+        None => jwt.to_string(),
+    };
+    let [header_b64, payload_b64, signature_b64] = jwt.trim().split('.').collect::<Vec<_>>()[..]
+    else {
+        anyhow::bail!("JWT must have three dot-separated parts");
+    };
+    let b64 = base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    let signature = b64
+        .decode(signature_b64)
+        .context("JWT signature is not base64url")?;
+    let payload = b64
+        .decode(payload_b64)
+        .context("JWT payload is not base64url")?;
+    let payload: serde_json::Value =
+        serde_json::from_slice(&payload).context("JWT payload is not JSON")?;
+
+    let pk_der = read_public_key_der(key)?;
+    let salt: [u8; 32] = hex::decode(salt.trim_start_matches("0x"))
+        .context("invalid salt hex")?
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("salt must be 32 bytes"))?;
+
+    let expected_claim = nonce_binding.expected_claim_value(jwt_nonce);
+    let nonce_claim = nonce_binding.claim_name();
+    let claim = payload.get(nonce_claim).and_then(serde_json::Value::as_str);
+    anyhow::ensure!(
+        claim == Some(expected_claim.as_str()),
+        "JWT `{nonce_claim}` claim {claim:?} does not bind this transaction (expected {expected_claim})"
+    );
+
+    Ok(JwtInputs {
+        pk_der,
+        header_b64: header_b64.to_string(),
+        payload_b64: payload_b64.to_string(),
+        signature,
+        salt,
+        nonce_claim: nonce_claim.to_string(),
+    })
+}
+
+fn outputs_json(outputs: &PublicOutputs) -> serde_json::Value {
+    serde_json::json!({
+        "subjectCommitment": hex::encode(outputs.subject_commitment),
+        "pkHash": hex::encode(outputs.pk_hash),
+        "iss": outputs.iss,
+        "aud": outputs.aud,
+        "nonce": outputs.nonce,
+        "iat": outputs.iat,
+        "exp": outputs.exp,
+        "alg": outputs.alg,
+        "nonceClaim": outputs.nonce_claim,
+    })
+}
+
+fn json_merge(document: &mut serde_json::Value, fields: serde_json::Value) {
+    if let (Some(document), serde_json::Value::Object(fields)) = (document.as_object_mut(), fields)
+    {
+        document.extend(fields);
+    }
+}
+
+fn verify(
+    path: &str,
+    transaction: Option<&str>,
+    registries: &[String],
+    now: Option<u64>,
+) -> Result<()> {
+    let contents = std::fs::read_to_string(path).with_context(|| format!("reading {path}"))?;
+    let document: serde_json::Value = serde_json::from_str(&contents).context("invalid JSON")?;
+    let field = |name: &str| {
+        document
+            .get(name)
+            .and_then(serde_json::Value::as_str)
+            .with_context(|| format!("proof JSON has no `{name}`"))
+    };
+    let vkey_hash: [u8; 32] = hex::decode(field("vkeyHash")?.trim_start_matches("0x"))
+        .context("invalid vkeyHash hex")?
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("vkeyHash must be 32 bytes"))?;
+    let groth16_proof = Sp1Groth16Proof {
+        proof: hex::decode(field("proof")?).context("invalid proof hex")?,
+        public_values: hex::decode(field("publicValues")?).context("invalid publicValues hex")?,
+        vkey_hash,
+    };
+
+    // The program's verifiers, so a proof accepted here is accepted on-chain
+    // (modulo the vkey registry, which only the cluster knows).
+    let jwts = if groth16_proof.public_values.first() == Some(&AGGREGATED_OUTPUTS_VERSION) {
+        verify_zk_oidc_aggregated_proof(&groth16_proof)
+    } else {
+        verify_zk_oidc_proof(&groth16_proof).map(|jwt| vec![jwt])
+    }
+    .map_err(|error| anyhow::anyhow!("proof rejected: {error}"))?;
+    println!("proof: valid for vkey 0x{}", hex::encode(vkey_hash));
+    for jwt in &jwts {
+        println!("iss: {}", jwt.iss);
+        println!("aud: {}", jwt.aud);
+        println!("nonce: {}", jwt.nonce);
+        println!("iat: {}", jwt.iat);
+        println!("exp: {}", jwt.exp);
+        println!("alg: {}", jwt.alg);
+        println!("nonce_claim: {}", jwt.nonce_claim);
+        println!(
+            "subject_commitment: {}",
+            hex::encode(jwt.subject_commitment)
+        );
+        println!("pk_hash: {}", hex::encode(jwt.pk_hash));
+    }
+
+    let transaction_bytes = match transaction {
+        Some(transaction) => read_transaction(transaction)?,
+        None => read_transaction(path)?,
+    };
+    Transaction::try_from_slice(&transaction_bytes)
+        .context("transaction is not a Borsh Transaction")?;
+    let transaction_nonce = transaction_nonce_hex(&Sha256::digest(&transaction_bytes).into());
+    println!("transaction nonce: {transaction_nonce}");
+
+    if registries.is_empty() {
+        // Without the registry the binding mode is unknown; `raw` is the default.
+        anyhow::ensure!(
+            jwts.iter().all(|jwt| jwt.nonce == transaction_nonce),
+            "JWT nonce does not bind this transaction (raw binding assumed; pass --registry)"
+        );
+        println!("ok: proof binds the transaction (raw nonce binding)");
+        return Ok(());
+    }
+    anyhow::ensure!(
+        registries.len() == 1 || registries.len() == jwts.len(),
+        "pass --registry once, or once per JWT of the proof"
+    );
+
+    let now = match now {
+        Some(now) => now,
+        None => std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs(),
+    };
+    for (index, jwt) in jwts.iter().enumerate() {
+        let registry_path = registries.get(index).unwrap_or(&registries[0]);
+        let data =
+            std::fs::read(registry_path).with_context(|| format!("reading {registry_path}"))?;
+        let registry = OidcKeyRegistry::try_deserialize(&mut data.as_slice())
+            .context("not an OidcKeyRegistry account")?;
+        if let Some(key_entry) = registry.find(&jwt.pk_hash) {
+            print_entry("key", key_entry);
+        }
+        // The program's own checks, so a pass here is a pass on-chain.
+        check_oidc_registry(jwt, &registry, &transaction_nonce, now)
+            .map_err(|err| anyhow::anyhow!("{err}"))
+            .with_context(|| format!("JWT #{index} ({})", jwt.iss))?;
+    }
+    println!("ok: execute_zk_oidc would accept this proof for the transaction at {now}");
+    Ok(())
+}
+
+/// SPKI DER of a JWK JSON file or a `PUBLIC KEY` PEM file.
+fn read_public_key_der(path: &str) -> Result<Vec<u8>> {
+    let contents = std::fs::read_to_string(path).with_context(|| format!("reading {path}"))?;
+
+    if contents.trim_start().starts_with("-----BEGIN PUBLIC KEY") {
+        // PEM is base64 (standard alphabet) of the DER between the armor lines.
+        let body: String = contents
+            .lines()
+            .filter(|line| !line.starts_with("-----"))
+            .collect();
+        return base64::engine::general_purpose::STANDARD
+            .decode(body.trim())
+            .context("invalid PEM body");
+    }
+
+    let jwk: jwks::Jwk = serde_json::from_str(&contents).context("key is neither PEM nor a JWK")?;
+    let (_, der) = jwk.to_spki_der()?;
+    Ok(der)
+}
+
+/// Borsh bytes of `--transaction`: inline hex, or a JSON file's `transactionBorsh`.
+fn read_transaction(transaction: &str) -> Result<Vec<u8>> {
+    if let Ok(bytes) = hex::decode(transaction.trim_start_matches("0x")) {
+        return Ok(bytes);
+    }
+
+    let contents =
+        std::fs::read_to_string(transaction).with_context(|| format!("reading {transaction}"))?;
+    let document: serde_json::Value = serde_json::from_str(&contents)?;
+    let borsh_hex = document
+        .get("transactionBorsh")
+        .and_then(serde_json::Value::as_str)
+        .context("JSON file has no `transactionBorsh`")?;
+    hex::decode(borsh_hex).context("invalid transactionBorsh hex")
+}
+
+fn parse_nonce_binding(value: &str) -> std::result::Result<OidcNonceBinding, String> {
+    match value {
+        "raw" => Ok(OidcNonceBinding::Raw),
+        "sha256" => Ok(OidcNonceBinding::Sha256),
+        _ => value
+            .strip_prefix("claim:")
+            .filter(|claim| !claim.is_empty())
+            .map(|claim| OidcNonceBinding::CustomClaim(claim.to_string()))
+            .ok_or_else(|| format!("expected raw, sha256 or claim:<name>, got {value}")),
+    }
+}
+
+/// Guest inputs for a test JWT of `sub`, signed by the fixture RSA key and
+/// binding the transaction whose nonce is `jwt_nonce`.
+fn test_jwt_inputs(
+    private_key: &RsaPrivateKey,
+    sub: &str,
+    salt: [u8; 32],
+    jwt_nonce: &str,
+) -> Result<JwtInputs> {
+    let pk_der = RsaPublicKey::from(private_key)
+        .to_public_key_der()?
+        .to_vec();
 
     let b64 = base64::engine::general_purpose::URL_SAFE_NO_PAD;
     let header_b64 = b64.encode(r#"{"alg":"RS256","typ":"JWT","kid":"test-key-1"}"#);
     let payload_json = serde_json::json!({
         "iss": TEST_ISS,
         "aud": TEST_AUD,
-        "sub": "test-subject-1",
+        "sub": sub,
         "email": TEST_EMAIL,
         "nonce": jwt_nonce,
-        "iat": 1749700000u64,
-        "exp": 1749703600u64,
+        "iat": TEST_IAT,
+        "exp": TEST_EXP,
     });
     let payload_b64 = b64.encode(payload_json.to_string());
 
@@ -143,72 +662,187 @@ fn fixture(
         &Sha256::digest(signing_input.as_bytes()),
     )?;
 
-    let mut stdin = SP1Stdin::new();
-    stdin.write(&pk_der);
-    stdin.write(&header_b64.as_bytes().to_vec());
-    stdin.write(&payload_b64.as_bytes().to_vec());
-    stdin.write(&signature);
+    Ok(JwtInputs {
+        pk_der,
+        header_b64,
+        payload_b64,
+        signature,
+        salt,
+        nonce_claim: NONCE_CLAIM.to_string(),
+    })
+}
 
-    let client = ProverClient::from_env();
+/// One JWT's private guest inputs.
+struct JwtInputs {
+    pk_der: Vec<u8>,
+    header_b64: String,
+    payload_b64: String,
+    signature: Vec<u8>,
+    salt: [u8; 32],
+    nonce_claim: String,
+}
 
-    if execute_only {
-        let (mut public_values, report) = client.execute(JWT_PROGRAM_ELF, &stdin).run()?;
-        let outputs: PublicOutputs = public_values.read();
-        println!("cycles: {}", report.total_instruction_count());
-        // Confirm the RSA/SHA precompiles are actually firing — a zero
-        // U256XU2048_MUL count would mean the patch isn't wired and the modexp
-        // is running as pure-Rust bignum (~5-6M cycles instead of ~967k).
-        let mut syscalls: Vec<_> = report
-            .syscall_counts
-            .iter()
-            .filter_map(|(k, &v)| if v > 0 { Some((k, v)) } else { None })
-            .collect();
-        syscalls.sort_by_key(|&(_, v)| std::cmp::Reverse(v));
-        println!("syscalls:");
-        for (code, count) in &syscalls {
-            println!("  {code:?}: {count}");
+impl JwtInputs {
+    /// In the order `read_and_verify_jwt` reads them.
+    fn write_to(&self, stdin: &mut SP1Stdin) {
+        stdin.write(&self.pk_der);
+        stdin.write(&self.header_b64.as_bytes().to_vec());
+        stdin.write(&self.payload_b64.as_bytes().to_vec());
+        stdin.write(&self.signature);
+        stdin.write(&self.salt);
+        stdin.write(&self.nonce_claim);
+    }
+}
+
+/// Stdin for `jwt-program` given one JWT, or for `jwt-aggregate` (a `u32`
+/// count, then each JWT's inputs) given several.
+fn guest_stdin(jwts: &[JwtInputs]) -> SP1Stdin {
+    let mut stdin = SP1Stdin::new();
+    if let [jwt] = jwts {
+        jwt.write_to(&mut stdin);
+    } else {
+        stdin.write(&(jwts.len() as u32));
+        for jwt in jwts {
+            jwt.write_to(&mut stdin);
         }
-        print_outputs(&outputs);
-        anyhow::ensure!(outputs.nonce == jwt_nonce, "guest nonce mismatch");
-        return Ok(());
     }
+    stdin
+}
 
-    let (pk, vk) = client.setup(JWT_PROGRAM_ELF);
-    let mut proof = client.prove(&pk, &stdin).groth16().run()?;
+/// Runs the guest without proving, reports its cycle count and returns its
+/// public values.
+fn execute_guest(elf: &[u8], stdin: &SP1Stdin) -> Result<Vec<u8>> {
+    let client = ProverClient::from_env();
+    let (public_values, report) = client.execute(elf, stdin).run()?;
+    println!("cycles: {}", report.total_instruction_count());
+    // Confirm the RSA/SHA precompiles are actually firing — a zero
+    // U256XU2048_MUL count would mean the patch isn't wired and the modexp
+    // is running as pure-Rust bignum (~5-6M cycles instead of ~967k).
+    let mut syscalls: Vec<_> = report
+        .syscall_counts
+        .iter()
+        .filter_map(|(k, &v)| if v > 0 { Some((k, v)) } else { None })
+        .collect();
+    syscalls.sort_by_key(|&(_, v)| std::cmp::Reverse(v));
+    println!("syscalls:");
+    for (code, count) in &syscalls {
+        println!("  {code:?}: {count}");
+    }
+    Ok(public_values.to_vec())
+}
+
+/// Produces and locally verifies a Groth16 proof of the guest.
+fn prove_guest(
+    elf: &[u8],
+    stdin: &SP1Stdin,
+) -> Result<(SP1ProofWithPublicValues, SP1VerifyingKey)> {
+    let client = ProverClient::from_env();
+    let (pk, vk) = client.setup(elf);
+    let mut proof = client.prove(&pk, stdin).groth16().run()?;
     client.verify(&proof, &vk)?;
 
     // The TEE attestation is not part of the on-chain Groth16 verification.
     proof.tee_proof = None;
 
-    let mut public_values = proof.public_values.clone();
-    let outputs: PublicOutputs = public_values.read();
-    print_outputs(&outputs);
-    anyhow::ensure!(outputs.nonce == jwt_nonce, "guest nonce mismatch");
+    Ok((proof, vk))
+}
 
-    let fixture = serde_json::json!({
-        "proof": hex::encode(proof.bytes()),
-        "publicValues": hex::encode(proof.public_values.to_vec()),
-        "vkeyHash": vk.bytes32(),
-        "emailHash": hex::encode(outputs.email_hash),
-        "pkHash": hex::encode(outputs.pk_hash),
-        "iss": outputs.iss,
-        "aud": outputs.aud,
-        "nonce": outputs.nonce,
-        "accountId": account_id.to_string(),
-        "accountNonce": account_nonce.to_string(),
-        "ethAddress": eth_address,
-        "transactionBorsh": hex::encode(&transaction_bytes),
-    });
+/// Decodes and prints the public values of either guest, one entry per JWT.
+fn decode_outputs(public_values: &[u8]) -> Result<Vec<PublicOutputs>> {
+    let outputs = if public_values.first() == Some(&AGGREGATED_OUTPUTS_VERSION) {
+        AggregatedOutputs::decode(public_values).map(|aggregated| aggregated.outputs)
+    } else {
+        PublicOutputs::decode(public_values).map(|outputs| vec![outputs])
+    }
+    .map_err(|error| anyhow::anyhow!("{error}"))?;
+
+    for outputs in &outputs {
+        print_outputs(outputs);
+    }
+    Ok(outputs)
+}
+
+fn jwks(path: &str, iss: &str, registry: Option<&str>, out: Option<&str>) -> Result<()> {
+    let jwks: jwks::Jwks = serde_json::from_str(
+        &std::fs::read_to_string(path).with_context(|| format!("reading {path}"))?,
+    )
+    .context("invalid JWKS JSON")?;
+    let entries = jwks::registry_entries(&jwks)?;
+    let iss_hash = OidcKeyRegistry::iss_hash(iss);
+
+    println!("iss: {iss}");
+    println!("iss_hash: {}", hex::encode(iss_hash));
+    for entry in &entries {
+        print_entry("key", entry);
+    }
+
+    if let Some(registry_path) = registry {
+        let data =
+            std::fs::read(registry_path).with_context(|| format!("reading {registry_path}"))?;
+        let registry = OidcKeyRegistry::try_deserialize(&mut data.as_slice())
+            .context("not an OidcKeyRegistry account")?;
+        anyhow::ensure!(
+            registry.iss == iss,
+            "registry is for issuer {}, not {iss}",
+            registry.iss
+        );
+
+        let diff = jwks::diff(&entries, &registry);
+        for entry in &diff.unchanged {
+            print_entry("=", entry);
+        }
+        for entry in &diff.add {
+            print_entry("+ add_oidc_key", entry);
+        }
+        for entry in &diff.remove {
+            print_entry("- remove_oidc_key", entry);
+        }
+    }
+
+    if let Some(out) = out {
+        // Field names follow the TS client's `add_oidc_key` argument; `keyType`
+        // is the Anchor enum variant name (`{ [keyType]: {} }`).
+        let keys: Vec<_> = entries
+            .iter()
+            .map(|entry| {
+                serde_json::json!({
+                    "kid": entry.kid,
+                    "keyType": format!("{:?}", entry.key_type).to_lowercase(),
+                    "pkHash": hex::encode(entry.pk_hash),
+                    "notBefore": entry.not_before.to_string(),
+                    "notAfter": entry.not_after.to_string(),
+                })
+            })
+            .collect();
+        let document = serde_json::json!({
+            "iss": iss,
+            "issHash": hex::encode(iss_hash),
+            "keys": keys,
+        });
+        std::fs::write(out, serde_json::to_string_pretty(&document)?)?;
+        println!("entries written to {out}");
+    }
 
-    std::fs::write(out, serde_json::to_string_pretty(&fixture)?)?;
-    println!("fixture written to {out} (proof size: {} bytes)", proof.bytes().len());
     Ok(())
 }
 
+fn print_entry(label: &str, entry: &OidcKeyEntry) {
+    println!(
+        "{label}: kid={} type={:?} pk_hash={}",
+        entry.kid,
+        entry.key_type,
+        hex::encode(entry.pk_hash)
+    );
+}
+
 fn print_outputs(outputs: &PublicOutputs) {
     println!("iss: {}", outputs.iss);
     println!("aud: {}", outputs.aud);
     println!("nonce: {}", outputs.nonce);
-    println!("email_hash: {}", hex::encode(outputs.email_hash));
+    println!("iat: {}", outputs.iat);
+    println!("exp: {}", outputs.exp);
+    println!("alg: {}", outputs.alg);
+    println!("nonce_claim: {}", outputs.nonce_claim);
+    println!("subject_commitment: {}", hex::encode(outputs.subject_commitment));
     println!("pk_hash: {}", hex::encode(outputs.pk_hash));
 }